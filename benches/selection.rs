@@ -0,0 +1,171 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use permissive_json_pointer::{map_leaf_values, select_values, SelectorSet};
+use serde_json::{json, Map, Value};
+
+/// A single object with a thousand scalar fields.
+fn wide_flat() -> Map<String, Value> {
+    (0..1000)
+        .map(|i| (format!("field{i}"), json!(format!("value {i}"))))
+        .collect()
+}
+
+/// A hundred levels of nested objects, each one with a few siblings.
+fn deep_nesting() -> Map<String, Value> {
+    let mut value = json!("leaf");
+    for i in (0..100).rev() {
+        value = json!({
+            "child": value,
+            "name": format!("level {i}"),
+            "depth": i,
+        });
+    }
+    Map::from_iter([(String::from("child"), value)])
+}
+
+/// Ten thousand documents in an array, like a search response.
+fn array_of_objects() -> Map<String, Value> {
+    let hits: Vec<Value> = (0..10_000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "title": format!("title {i}"),
+                "author": { "name": format!("author {i}"), "born": 1900 + i % 100 },
+                "tags": ["fiction", "classic"],
+            })
+        })
+        .collect();
+    Map::from_iter([(String::from("hits"), Value::Array(hits))])
+}
+
+/// The same paths written with both nested objects and flattened keys.
+fn dotted_keys() -> Map<String, Value> {
+    (0..200)
+        .flat_map(|i| {
+            [
+                (format!("pet{i}.dog.name"), json!("jean")),
+                (format!("pet{i}.dog"), json!({ "name": "bob", "age": i })),
+                (
+                    format!("pet{i}"),
+                    json!({ "dog.name": "michel", "dog": { "name": "milan" } }),
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn corpora() -> Vec<(&'static str, Map<String, Value>, SelectorSet)> {
+    let selectors = |selectors: &[&str]| SelectorSet::parse(selectors.iter().copied()).unwrap();
+    let many_selectors: Vec<String> = (0..500).map(|i| format!("field{}", i * 2)).collect();
+    let deep_selector = vec!["child"; 100].join(".");
+
+    vec![
+        (
+            "wide flat",
+            wide_flat(),
+            selectors(&["field1", "field500", "field999"]),
+        ),
+        (
+            "many selectors",
+            wide_flat(),
+            selectors(
+                &many_selectors
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        ("deep nesting", deep_nesting(), selectors(&[&deep_selector])),
+        (
+            "array of objects",
+            array_of_objects(),
+            selectors(&["hits.title", "hits.author.name"]),
+        ),
+        (
+            "dotted keys",
+            dotted_keys(),
+            selectors(&["pet0.dog.name", "pet100.dog", "pet199"]),
+        ),
+    ]
+}
+
+fn bench_select_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("select_values");
+    for (name, document, selectors) in corpora() {
+        group.bench_function(name, |b| {
+            b.iter(|| select_values(black_box(&document), &selectors))
+        });
+    }
+    group.finish();
+}
+
+fn bench_map_leaf_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_leaf_values");
+    for (name, document, selectors) in corpora() {
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || document.clone(),
+                |document| {
+                    map_leaf_values(document, &selectors, |_, value| {
+                        *value = Value::Null;
+                    })
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_selector_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selector_set");
+    for (name, _, selectors) in corpora() {
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || selectors.clone(),
+                |selectors| selectors.normalize(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+fn bench_batch(c: &mut Criterion) {
+    use permissive_json_pointer::{map_leaf_values_batch, select_values_batch};
+
+    let documents: Vec<Map<String, Value>> = array_of_objects()["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit.as_object().unwrap().clone())
+        .collect();
+    let selectors = SelectorSet::parse(["title", "author.name"]).unwrap();
+
+    let mut group = c.benchmark_group("batch");
+    group.bench_function("select_values_batch", |b| {
+        b.iter(|| select_values_batch(black_box(&documents), &selectors))
+    });
+    group.bench_function("map_leaf_values_batch", |b| {
+        b.iter_batched_ref(
+            || documents.clone(),
+            |documents| {
+                map_leaf_values_batch(documents, &selectors, |_, value| *value = Value::Null)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+fn bench_batch(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_select_values,
+    bench_map_leaf_values,
+    bench_selector_set,
+    bench_batch
+);
+criterion_main!(benches);
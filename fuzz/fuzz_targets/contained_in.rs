@@ -0,0 +1,31 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use permissive_json_pointer::{explain, select_values};
+use serde_json::{Map, Value};
+
+/// Arbitrary keys and selectors, where multi-byte characters next to the `.` stress the byte
+/// slicing done when a selector is matched against a dotted key.
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    key: &'a str,
+    selector: &'a str,
+}
+
+fuzz_target!(|input: Input| {
+    let mut document = Map::new();
+    document.insert(input.key.to_string(), Value::Bool(true));
+
+    let selected = select_values(&document, [input.selector]);
+    let expected = input.key == input.selector
+        || input
+            .key
+            .strip_prefix(input.selector)
+            .is_some_and(|rest| rest.starts_with('.'));
+    assert_eq!(!selected.is_empty(), expected);
+
+    // explain matches what select_values selects
+    let traces = explain(&document, input.selector);
+    assert_eq!(traces.is_empty(), selected.is_empty());
+});
@@ -0,0 +1,56 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use permissive_json_pointer::{
+    explain, field_paths, get_values, map_leaf_values, map_leaf_values_with_options, select_values,
+    select_values_with_options, set_value, MissingParents, SelectOptions,
+};
+use serde_json::{Map, Value};
+
+// Every byte of the input is one level of nesting, even bytes are objects and odd bytes are
+// arrays.
+fuzz_target!(|levels: &[u8]| {
+    let mut value = Value::Null;
+    let mut selector = Vec::new();
+    for level in levels.iter().rev() {
+        value = if level % 2 == 0 {
+            selector.push("a");
+            Value::Object(Map::from_iter([(String::from("a"), value)]))
+        } else {
+            Value::Array(vec![value])
+        };
+    }
+    let mut document = Map::from_iter([(String::from("a"), value)]);
+    selector.push("a");
+    let selector = selector.join(".");
+
+    let options = SelectOptions::default();
+    let too_deep = levels.len() + 1 > options.max_depth;
+
+    let selected = select_values_with_options(&document, [selector.as_str()], &options);
+    assert_eq!(selected.is_err(), too_deep);
+    let mapped =
+        map_leaf_values_with_options(&mut document, [selector.as_str()], &options, |_, _| ());
+    assert_eq!(mapped.is_err(), too_deep);
+
+    // the functions without options must accept any depth
+    let selected = select_values(&document, [selector.as_str()]);
+    assert_eq!(
+        selected.is_empty(),
+        get_values(&document, &selector).is_empty()
+    );
+    assert_eq!(
+        explain(&document, &selector).len(),
+        get_values(&document, &selector).len()
+    );
+    map_leaf_values(&mut document, [selector.as_str()], |_, value| {
+        *value = Value::Bool(true)
+    });
+    assert_eq!(field_paths(&document, true).len(), 1);
+    set_value(
+        &mut document,
+        &selector,
+        Value::Null,
+        MissingParents::Nested,
+    );
+});
@@ -0,0 +1,32 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use permissive_json_pointer::{map_leaf_values, select_values};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    document: &'a str,
+    selectors: Vec<&'a str>,
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(mut document) = serde_json::from_str::<Map<String, Value>>(input.document) else {
+        return;
+    };
+    let selected = select_values(&document, input.selectors.iter().copied());
+
+    // replacing the selected leaves by null must not change the shape of the selection
+    map_leaf_values(
+        &mut document,
+        input.selectors.iter().copied(),
+        |_, value| *value = Value::Null,
+    );
+    let mapped = select_values(&document, input.selectors.iter().copied());
+    assert_eq!(field_count(&selected), field_count(&mapped));
+});
+
+fn field_count(value: &Map<String, Value>) -> usize {
+    permissive_json_pointer::field_paths(value, false).len()
+}
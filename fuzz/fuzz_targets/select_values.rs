@@ -0,0 +1,45 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use permissive_json_pointer::{
+    select_values_with_options, OutputShape, Placeholder, ScalarsInArrays, SelectOptions,
+};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    document: &'a str,
+    selectors: Vec<&'a str>,
+    flattened: bool,
+    placeholder: u8,
+    scalars_in_arrays: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(document) = serde_json::from_str::<Map<String, Value>>(input.document) else {
+        return;
+    };
+
+    let options = SelectOptions {
+        shape: if input.flattened {
+            OutputShape::Flattened
+        } else {
+            OutputShape::Nested
+        },
+        placeholder: match input.placeholder % 3 {
+            0 => Placeholder::Remove,
+            1 => Placeholder::Empty,
+            _ => Placeholder::Null,
+        },
+        scalars_in_arrays: match input.scalars_in_arrays % 3 {
+            0 => ScalarsInArrays::Drop,
+            1 => ScalarsInArrays::Keep,
+            _ => ScalarsInArrays::Null,
+        },
+        ..SelectOptions::default()
+    };
+
+    // serde_json refuses to parse a json deeper than the default maximum depth
+    select_values_with_options(&document, input.selectors.iter().copied(), &options).unwrap();
+});
@@ -0,0 +1,310 @@
+use rayon::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::limit::Budget;
+use crate::{
+    check_depth, collect_selectors, create_value, map_object, select_in_object, LimitError,
+    SelectOptions, SelectorSet,
+};
+
+/// Same as [`select_values`](crate::select_values) but selects the values of many jsons in
+/// parallel. The selectors are shared by all the threads, and the selections are returned in
+/// the order of the jsons.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values_batch, SelectorSet};
+///
+/// let values = [
+///     json!({ "name": "peanut", "age": 8 }),
+///     json!({ "name": "patou", "race": { "name": "bernese mountain" } }),
+/// ];
+/// let values: Vec<Map<String, Value>> =
+///     values.into_iter().map(|value| value.as_object().unwrap().clone()).collect();
+/// let selectors = SelectorSet::parse(["name"]).unwrap();
+///
+/// let res = select_values_batch(&values, &selectors);
+/// assert_eq!(
+///     Value::from(res),
+///     json!([{ "name": "peanut" }, { "name": "patou" }]),
+/// );
+/// ```
+pub fn select_values_batch(
+    values: &[Map<String, Value>],
+    selectors: &SelectorSet,
+) -> Vec<Map<String, Value>> {
+    let selectors: Vec<&str> = selectors.iter().map(AsRef::as_ref).collect();
+    let options = SelectOptions::default();
+
+    values
+        .par_iter()
+        .map(|value| {
+            create_value(value, &selectors, "", &options, &mut Budget::unlimited())
+                .expect("an unlimited budget can't be exceeded")
+        })
+        .collect()
+}
+
+/// Same as [`select_values_batch`] but lets you customize the selection with
+/// [`SelectOptions`], like [`select_values_with_options`](crate::select_values_with_options).
+/// The limits apply to each json separately, an error is returned if any of the jsons exceeds
+/// one of them.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values_batch_with_options, LimitError, SelectOptions, SelectorSet};
+///
+/// let values = [
+///     json!({ "name": "peanut", "age": 8 }),
+///     json!({ "name": "patou", "race": { "name": "bernese mountain" } }),
+/// ];
+/// let values: Vec<Map<String, Value>> =
+///     values.into_iter().map(|value| value.as_object().unwrap().clone()).collect();
+/// let selectors = SelectorSet::parse(["name", "race.name"]).unwrap();
+///
+/// let options = SelectOptions { max_selected_nodes: Some(2), ..SelectOptions::default() };
+/// let res = select_values_batch_with_options(&values, &selectors, &options);
+/// assert_eq!(res, Err(LimitError::MaxSelectedNodes { max_selected_nodes: 2 }));
+///
+/// let options = SelectOptions { max_selected_nodes: Some(3), ..SelectOptions::default() };
+/// let res = select_values_batch_with_options(&values, &selectors, &options).unwrap();
+/// assert_eq!(
+///     Value::from(res),
+///     json!([{ "name": "peanut" }, { "name": "patou", "race": { "name": "bernese mountain" } }]),
+/// );
+/// ```
+pub fn select_values_batch_with_options(
+    values: &[Map<String, Value>],
+    selectors: &SelectorSet,
+    options: &SelectOptions,
+) -> Result<Vec<Map<String, Value>>, LimitError> {
+    let selectors = collect_selectors(selectors, options)?;
+
+    values
+        .par_iter()
+        .map(|value| {
+            check_depth(value, options.max_depth)?;
+            select_in_object(value, &selectors, options)
+        })
+        .collect()
+}
+
+/// Same as [`map_leaf_values`](crate::map_leaf_values) but maps the leaves of many jsons in
+/// parallel. Since the mapper is called from multiple threads at the same time it can't
+/// mutate its environment.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{map_leaf_values_batch, SelectorSet};
+///
+/// let mut values: Vec<Map<String, Value>> = (0..100)
+///     .map(|i| json!({ "name": format!("doggo {i}"), "age": i }))
+///     .map(|value| value.as_object().unwrap().clone())
+///     .collect();
+/// let selectors = SelectorSet::parse(["age"]).unwrap();
+///
+/// map_leaf_values_batch(&mut values, &selectors, |_, value| {
+///     *value = json!(value.as_u64().unwrap() * 7);
+/// });
+/// assert_eq!(values[3], *json!({ "name": "doggo 3", "age": 21 }).as_object().unwrap());
+/// ```
+pub fn map_leaf_values_batch(
+    values: &mut [Map<String, Value>],
+    selectors: &SelectorSet,
+    mapper: impl Fn(&str, &mut Value) + Sync,
+) {
+    let selectors: Vec<&str> = selectors.iter().map(AsRef::as_ref).collect();
+    let options = SelectOptions::default();
+
+    values.par_iter_mut().for_each(|value| {
+        map_object(value, &selectors, "", &options, &mut |key, value| {
+            mapper(key, value)
+        })
+    });
+}
+
+/// Same as [`map_leaf_values_batch`] but lets you customize which leaves are visited with
+/// [`SelectOptions`], like [`map_leaf_values_with_options`](crate::map_leaf_values_with_options).
+/// Returns an error without mapping any json if one of them exceeds the limits.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{map_leaf_values_batch_with_options, LimitError, SelectOptions, SelectorSet};
+///
+/// let mut values: Vec<Map<String, Value>> = (0..100)
+///     .map(|i| json!({ "name": format!("doggo {i}"), "age": i }))
+///     .map(|value| value.as_object().unwrap().clone())
+///     .collect();
+/// values[42].insert("toys".to_string(), json!([[{ "name": "ball" }]]));
+/// let selectors = SelectorSet::parse(["age"]).unwrap();
+/// let double = |_: &str, value: &mut Value| *value = json!(value.as_u64().unwrap() * 2);
+///
+/// let options = SelectOptions { max_depth: 3, ..SelectOptions::default() };
+/// let res = map_leaf_values_batch_with_options(&mut values, &selectors, &options, double);
+/// assert_eq!(res, Err(LimitError::MaxDepth { max_depth: 3 }));
+/// assert_eq!(values[3]["age"], json!(3));
+///
+/// let options = SelectOptions { max_depth: 4, ..SelectOptions::default() };
+/// map_leaf_values_batch_with_options(&mut values, &selectors, &options, double).unwrap();
+/// assert_eq!(values[3]["age"], json!(6));
+/// ```
+pub fn map_leaf_values_batch_with_options(
+    values: &mut [Map<String, Value>],
+    selectors: &SelectorSet,
+    options: &SelectOptions,
+    mapper: impl Fn(&str, &mut Value) + Sync,
+) -> Result<(), LimitError> {
+    let selectors = collect_selectors(selectors, options)?;
+    values
+        .par_iter()
+        .try_for_each(|value| check_depth(value, options.max_depth))?;
+
+    values.par_iter_mut().for_each(|value| {
+        map_object(value, &selectors, "", options, &mut |key, value| {
+            mapper(key, value)
+        })
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::deep::drop_value;
+    use crate::{
+        get_values, map_leaf_values, map_leaf_values_with_options, select_values,
+        select_values_with_options, OutputShape, ScalarsInArrays,
+    };
+
+    #[test]
+    fn same_as_sequential() {
+        let values: Vec<Map<String, Value>> = (0..1000)
+            .map(|i| {
+                json!({
+                    "name": format!("doggo {i}"),
+                    "race.name": "bernese mountain",
+                    "race": { "name": "bernese", "size": i },
+                    "toys": [{ "name": "ball" }, "stick"],
+                })
+            })
+            .map(|value| value.as_object().unwrap().clone())
+            .collect();
+        let selectors = SelectorSet::parse(["name", "race.name", "toys.name"]).unwrap();
+
+        let expected: Vec<_> = values
+            .iter()
+            .map(|value| select_values(value, &selectors))
+            .collect();
+        assert_eq!(select_values_batch(&values, &selectors), expected);
+
+        let mut expected = values.clone();
+        for value in &mut expected {
+            map_leaf_values(value, &selectors, |key, value| {
+                *value = json!(format!("{key}: {value}"))
+            });
+        }
+        let mut values = values;
+        map_leaf_values_batch(&mut values, &selectors, |key, value| {
+            *value = json!(format!("{key}: {value}"))
+        });
+        assert_eq!(values, expected);
+
+        assert!(select_values_batch(&[], &selectors).is_empty());
+    }
+
+    #[test]
+    fn with_options() {
+        let values: Vec<Map<String, Value>> = (0..1000)
+            .map(|i| {
+                json!({
+                    "name": format!("doggo {i}"),
+                    "race": { "name": "bernese", "size": i },
+                    "toys": [{ "name": "ball" }, "stick"],
+                })
+            })
+            .map(|value| value.as_object().unwrap().clone())
+            .collect();
+        let selectors = SelectorSet::parse(["name", "race", "toys"]).unwrap();
+        let options = SelectOptions {
+            shape: OutputShape::Flattened,
+            scalars_in_arrays: ScalarsInArrays::Keep,
+            max_selected_nodes: Some(8),
+            ..SelectOptions::default()
+        };
+
+        let expected: Vec<_> = values
+            .iter()
+            .map(|value| select_values_with_options(value, &selectors, &options).unwrap())
+            .collect();
+        assert_eq!(
+            select_values_batch_with_options(&values, &selectors, &options),
+            Ok(expected)
+        );
+
+        let mut expected = values.clone();
+        for value in &mut expected {
+            map_leaf_values_with_options(value, &selectors, &options, |key, value| {
+                *value = json!(format!("{key}: {value}"))
+            })
+            .unwrap();
+        }
+        let mut mapped = values.clone();
+        let res =
+            map_leaf_values_batch_with_options(&mut mapped, &selectors, &options, |key, value| {
+                *value = json!(format!("{key}: {value}"))
+            });
+        assert_eq!(res, Ok(()));
+        assert_eq!(mapped, expected);
+
+        // the limits apply to each json separately
+        let options = SelectOptions {
+            max_selected_nodes: Some(7),
+            ..options
+        };
+        assert_eq!(
+            select_values_batch_with_options(&values, &selectors, &options),
+            Err(LimitError::MaxSelectedNodes {
+                max_selected_nodes: 7
+            })
+        );
+        let options = SelectOptions {
+            max_selectors: Some(2),
+            ..options
+        };
+        assert_eq!(
+            select_values_batch_with_options(&[], &selectors, &options),
+            Err(LimitError::MaxSelectors { max_selectors: 2 })
+        );
+
+        // a single json too deep prevents the mapping of all of them
+        let mut mapped = values.clone();
+        mapped[500].insert(String::from("toys"), json!([[[{ "name": "ball" }]]]));
+        let options = SelectOptions {
+            max_depth: 4,
+            ..SelectOptions::default()
+        };
+        let res = map_leaf_values_batch_with_options(&mut mapped, &selectors, &options, |_, _| {
+            panic!("nothing must be mapped")
+        });
+        assert_eq!(res, Err(LimitError::MaxDepth { max_depth: 4 }));
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let depth = 10_000;
+        let mut value = Value::Null;
+        for _ in 0..depth {
+            value = Value::Object(Map::from_iter([(String::from("a"), value)]));
+        }
+        let mut values = vec![Map::from_iter([(String::from("a"), value)])];
+        let selectors = SelectorSet::parse(["a"]).unwrap();
+
+        for res in select_values_batch(&values, &selectors) {
+            assert_eq!(check_depth(&res, depth + 1), Ok(()));
+            drop_value(Value::Object(res));
+        }
+        map_leaf_values_batch(&mut values, &selectors, |_, value| *value = json!(1));
+        for value in values {
+            assert_eq!(get_values(&value, "a").len(), 1);
+            drop_value(Value::Object(value));
+        }
+    }
+}
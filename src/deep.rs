@@ -0,0 +1,119 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+
+use serde_json::{map, Map, Value};
+
+/// An object or an array being built from another one, with the iterator over the values
+/// still to go through.
+pub(crate) enum Partial<'a> {
+    Object(map::Iter<'a>, Map<String, Value>),
+    Array(slice::Iter<'a, Value>, Vec<Value>),
+}
+
+impl<'a> Partial<'a> {
+    pub(crate) fn of(value: &'a Value) -> Option<Self> {
+        match value {
+            Value::Object(object) => Some(Partial::Object(object.iter(), Map::new())),
+            Value::Array(array) => Some(Partial::Array(
+                array.iter(),
+                Vec::with_capacity(array.len()),
+            )),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<(Option<&'a String>, &'a Value)> {
+        match self {
+            Partial::Object(iter, _) => iter.next().map(|(key, value)| (Some(key), value)),
+            Partial::Array(iter, _) => iter.next().map(|value| (None, value)),
+        }
+    }
+
+    pub(crate) fn push(&mut self, key: Option<&String>, value: Value) {
+        match (self, key) {
+            (Partial::Object(_, object), Some(key)) => {
+                object.insert(key.clone(), value);
+            }
+            (Partial::Array(_, array), _) => array.push(value),
+            (Partial::Object(..), None) => unreachable!("a field of an object has a key"),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Value {
+        match self {
+            Partial::Object(_, object) => Value::Object(object),
+            Partial::Array(_, array) => Value::Array(array),
+        }
+    }
+}
+
+/// Same as [`Value::clone`] but doesn't recurse, so it can't overflow the stack however deep
+/// the value is.
+pub(crate) fn clone_value(value: &Value) -> Value {
+    let Some(root) = Partial::of(value) else {
+        return value.clone();
+    };
+
+    // the containers being cloned, with the key they must be inserted under in their parent
+    let mut stack = vec![(None, root)];
+    while let Some((_, partial)) = stack.last_mut() {
+        match partial.next() {
+            Some((key, value)) => match Partial::of(value) {
+                Some(child) => stack.push((key, child)),
+                None => partial.push(key, value.clone()),
+            },
+            None => {
+                let (key, partial) = stack.pop().unwrap();
+                let value = partial.finish();
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.push(key, value),
+                    None => return value,
+                }
+            }
+        }
+    }
+
+    unreachable!("the root is returned when it's finished")
+}
+
+/// Drops a value without recursing.
+pub(crate) fn drop_value(value: Value) {
+    let mut to_drop = vec![value];
+    while let Some(value) = to_drop.pop() {
+        match value {
+            Value::Object(object) => to_drop.extend(object.into_iter().map(|(_, value)| value)),
+            Value::Array(array) => to_drop.extend(array),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn clone_and_drop() {
+        let value = json!({
+            "name": "peanut",
+            "race": { "name": "bernese", "sizes": [80, [70, {}], []] },
+            "toys": [{ "name": "ball" }, "stick"],
+        });
+        assert_eq!(clone_value(&value), value);
+        assert_eq!(clone_value(&json!("patou")), json!("patou"));
+        assert_eq!(clone_value(&json!([])), json!([]));
+
+        let mut value = Value::Null;
+        for _ in 0..100_000 {
+            let object = Map::from_iter([(String::from("a"), value)]);
+            value = Value::Array(vec![Value::Object(object)]);
+        }
+        let clone = clone_value(&value);
+        drop_value(value);
+        drop_value(clone);
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::route::{find_routes, follow, Step};
+
+/// One location matched by a selector, returned by [`explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchTrace<'a> {
+    /// The steps followed from the root of the json to reach the value.
+    pub steps: Vec<Step>,
+    /// The matched value.
+    pub value: &'a Value,
+}
+
+impl MatchTrace<'_> {
+    /// Returns the chain of keys followed to reach the value, without the array indices.
+    pub fn keys(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Key(key) => Some(key.as_str()),
+                Step::Index(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Displays the steps as `["pet"]["dog.name"]`, array indices are displayed as `[0]`.
+impl fmt::Display for MatchTrace<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            match step {
+                Step::Key(key) => write!(f, "[{key:?}]")?,
+                Step::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists every location matched by a selector, in the order they appear in the json.
+/// A location is matched when [`select_values`](crate::select_values) selects it and doesn't
+/// select its parent, so a flattened key nested under the selector, like `dog.name` for the
+/// selector `dog`, is matched too.
+/// Useful to understand why a field appears in the result of [`select_values`](crate::select_values).
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::explain;
+///
+/// let value: Value = json!({
+///     "pet.dog": {
+///         "name": "bob",
+///     },
+///     "pet": {
+///         "dog.name": "michel",
+///     },
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// let traces = explain(value, "pet.dog.name");
+/// assert_eq!(traces[0].keys(), ["pet", "dog.name"]);
+/// assert_eq!(traces[0].value, "michel");
+/// assert_eq!(traces[1].keys(), ["pet.dog", "name"]);
+/// assert_eq!(traces[1].to_string(), r#"["pet.dog"]["name"]"#);
+/// ```
+pub fn explain(value: &Map<String, Value>, selector: impl AsRef<str>) -> Vec<MatchTrace<'_>> {
+    find_routes(value, selector.as_ref())
+        .into_iter()
+        .filter_map(|steps| {
+            let value = follow(value, &steps)?;
+            Some(MatchTrace { steps, value })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn explain_conflicts() {
+        let value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                }
+            },
+            "doggos": [
+                { "name": "marc" },
+                { "age": 4 },
+                [{ "name": ["patou"] }],
+            ],
+        });
+        let value: &Map<String, Value> = value.as_object().unwrap();
+
+        let traces = explain(value, "pet.dog.name");
+        assert_eq!(
+            traces.iter().map(|trace| trace.keys()).collect::<Vec<_>>(),
+            vec![
+                vec!["pet", "dog", "name"],
+                vec!["pet", "dog.name"],
+                vec!["pet.dog", "name"],
+                vec!["pet.dog.name"],
+            ]
+        );
+        assert_eq!(
+            traces.iter().map(|trace| trace.value).collect::<Vec<_>>(),
+            vec!["milan", "michel", "bob", "jean"]
+        );
+
+        let traces = explain(value, "doggos.name");
+        assert_eq!(
+            traces,
+            vec![
+                MatchTrace {
+                    steps: vec![Step::Key(S("doggos")), Step::Index(0), Step::Key(S("name"))],
+                    value: &json!("marc"),
+                },
+                MatchTrace {
+                    steps: vec![
+                        Step::Key(S("doggos")),
+                        Step::Index(2),
+                        Step::Index(0),
+                        Step::Key(S("name"))
+                    ],
+                    value: &json!(["patou"]),
+                },
+            ]
+        );
+        assert_eq!(traces[1].to_string(), r#"["doggos"][2][0]["name"]"#);
+
+        assert!(explain(value, "pet.cat").is_empty());
+    }
+}
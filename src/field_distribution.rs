@@ -0,0 +1,204 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde_json::{Map, Value};
+
+use crate::selector_set::normalize;
+use crate::{contained_in, field_paths};
+
+/// Counts, over many documents, how many documents contain each dotted path.
+/// A path is counted at most once per document even if it appears several times
+/// in it (through a flattened key or an array for example).
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::FieldDistribution;
+///
+/// let mut distribution = FieldDistribution::new();
+/// distribution.add(json!({ "name": "peanut", "race": { "name": "bernese" } }).as_object().unwrap());
+/// distribution.add(json!({ "name": "jean", "race.name": "golden" }).as_object().unwrap());
+///
+/// assert_eq!(distribution.documents(), 2);
+/// assert_eq!(distribution.get("name"), 2);
+/// assert_eq!(distribution.get("race"), 1);
+/// assert_eq!(distribution.get("race.name"), 2);
+/// assert_eq!(distribution.get("age"), 0);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FieldDistribution {
+    selectors: Option<Vec<String>>,
+    documents: u64,
+    distribution: BTreeMap<String, u64>,
+}
+
+impl FieldDistribution {
+    /// Creates an empty distribution counting every path of the documents.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty distribution that only counts the paths selected by the selectors.
+    /// A path is selected if it's one of the selectors or if it's nested in one of them.
+    /// The selectors are normalized like [`SelectorSet::normalize`](crate::SelectorSet::normalize),
+    /// so their order and the selectors nested in another one don't matter.
+    pub fn with_selectors<'a, S: AsRef<str> + ?Sized + 'a>(
+        selectors: impl IntoIterator<Item = &'a S>,
+    ) -> Self {
+        let mut selectors: Vec<String> = selectors
+            .into_iter()
+            .map(|selector| selector.as_ref().to_string())
+            .collect();
+        normalize(&mut selectors);
+
+        Self {
+            selectors: Some(selectors),
+            ..Self::default()
+        }
+    }
+
+    /// Counts all the paths of a document.
+    pub fn add(&mut self, document: &Map<String, Value>) {
+        self.documents += 1;
+
+        for path in field_paths(document, false) {
+            let selected = match &self.selectors {
+                Some(selectors) => selectors
+                    .iter()
+                    .any(|selector| contained_in(&path, selector)),
+                None => true,
+            };
+
+            if selected {
+                *self.distribution.entry(path).or_default() += 1;
+            }
+        }
+    }
+
+    /// Adds the counts of another distribution to this one.
+    /// Useful to merge the distributions computed on different threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distributions don't count the same paths, since their counts couldn't
+    /// be compared. Both must be created with [`FieldDistribution::new`], or with
+    /// [`FieldDistribution::with_selectors`] and the same selectors in any order.
+    pub fn merge(&mut self, other: FieldDistribution) {
+        assert_eq!(
+            self.selectors, other.selectors,
+            "Merging distributions created with different selectors."
+        );
+        self.documents += other.documents;
+        for (path, count) in other.distribution {
+            *self.distribution.entry(path).or_default() += count;
+        }
+    }
+
+    /// Returns the number of documents that were added to the distribution.
+    pub fn documents(&self) -> u64 {
+        self.documents
+    }
+
+    /// Returns the number of documents containing the path.
+    pub fn get(&self, path: &str) -> u64 {
+        self.distribution.get(path).copied().unwrap_or_default()
+    }
+
+    /// Iterates over all the paths and their count, sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.distribution
+            .iter()
+            .map(|(path, count)| (path.as_str(), *count))
+    }
+
+    /// Returns the count of each path.
+    pub fn into_inner(self) -> BTreeMap<String, u64> {
+        self.distribution
+    }
+}
+
+impl<'a> Extend<&'a Map<String, Value>> for FieldDistribution {
+    fn extend<T: IntoIterator<Item = &'a Map<String, Value>>>(&mut self, documents: T) {
+        documents
+            .into_iter()
+            .for_each(|document| self.add(document));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn count_and_merge() {
+        let documents = [
+            json!({
+                "name": "peanut",
+                "race": {
+                    "name": "bernese mountain",
+                    "size": "80cm",
+                },
+            }),
+            json!({
+                "name": "jean",
+                "race.name": "golden retriever",
+                "race": {
+                    "name": "golden retriever",
+                },
+            }),
+            json!({
+                "doggos": [
+                    { "name": "marc" },
+                    { "name": "bob", "age": 4 },
+                ],
+            }),
+        ];
+        let documents: Vec<_> = documents
+            .iter()
+            .map(|document| document.as_object().unwrap())
+            .collect();
+
+        let mut distribution = FieldDistribution::new();
+        distribution.extend(documents.iter().copied());
+        assert_eq!(distribution.documents(), 3);
+        assert_eq!(
+            distribution.iter().collect::<Vec<_>>(),
+            vec![
+                ("doggos", 1),
+                ("doggos.age", 1),
+                ("doggos.name", 1),
+                ("name", 2),
+                ("race", 2),
+                ("race.name", 2),
+                ("race.size", 1),
+            ]
+        );
+
+        let mut left = FieldDistribution::with_selectors(["race", "doggos.age"]);
+        left.add(documents[0]);
+        // the order of the selectors and the nested selectors don't matter
+        let mut right = FieldDistribution::with_selectors(["doggos.age", "race.name", "race"]);
+        right.extend(documents[1..].iter().copied());
+        left.merge(right);
+
+        assert_eq!(left.documents(), 3);
+        assert_eq!(
+            left.into_inner().into_iter().collect::<Vec<_>>(),
+            vec![
+                (S("doggos.age"), 1),
+                (S("race"), 2),
+                (S("race.name"), 2),
+                (S("race.size"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic = "Merging distributions created with different selectors."]
+    fn merge_different_selectors() {
+        let mut distribution = FieldDistribution::with_selectors(["name"]);
+        distribution.merge(FieldDistribution::with_selectors(["race"]));
+    }
+}
@@ -1,8 +1,47 @@
 #![doc = include_str!("../README.md")]
-
-use std::collections::HashSet;
-
-use serde_json::*;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "rayon")]
+mod batch;
+mod deep;
+mod explain;
+mod field_distribution;
+mod limit;
+mod projection;
+mod report;
+mod route;
+mod selection;
+mod selector;
+mod selector_set;
+
+#[cfg(feature = "rayon")]
+pub use batch::{
+    map_leaf_values_batch, map_leaf_values_batch_with_options, select_values_batch,
+    select_values_batch_with_options,
+};
+pub use explain::{explain, MatchTrace};
+pub use field_distribution::FieldDistribution;
+pub use limit::{check_depth, LimitError};
+pub use projection::{ProjectedField, Projection, ProjectionError};
+pub use report::{select_values_with_report, SelectionReport};
+pub use route::Step;
+pub use selector::{Selector, SelectorError};
+pub use selector_set::{SelectorListError, SelectorSet};
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+use deep::{clone_value, drop_value, Partial};
+use limit::{Budget, DEFAULT_MAX_DEPTH};
+use route::{find_exact_routes, find_routes, follow, follow_mut, Route};
+use selection::{join_path, Selection};
+use serde_json::{Map, Value};
 
 type Document = Map<String, Value>;
 
@@ -53,61 +92,180 @@ fn contained_in(selector: &str, key: &str) -> bool {
 ///     })
 /// );
 /// ```
-pub fn map_leaf_values<'a>(
+pub fn map_leaf_values<'a, S: AsRef<str> + ?Sized + 'a>(
     value: &mut Map<String, Value>,
-    selectors: impl IntoIterator<Item = &'a str>,
+    selectors: impl IntoIterator<Item = &'a S>,
     mut mapper: impl FnMut(&str, &mut Value),
 ) {
-    let selectors: Vec<_> = selectors.into_iter().collect();
-    map_leaf_values_in_object(value, &selectors, "", &mut mapper);
+    let selectors: Vec<_> = selectors.into_iter().map(AsRef::as_ref).collect();
+    map_object(
+        value,
+        &selectors,
+        "",
+        &SelectOptions::default(),
+        &mut mapper,
+    );
+}
+
+/// Same as [`map_leaf_values`] but lets you customize which leaves are visited with
+/// [`SelectOptions`]. Only [`SelectOptions::scalars_in_arrays`], [`SelectOptions::max_depth`]
+/// and [`SelectOptions::max_selectors`] have an effect on the mapping.
+/// Returns an error without mapping anything if the json exceeds one of the limits.
+/// ```
+/// use serde_json::{Value, json};
+/// use permissive_json_pointer::{map_leaf_values_with_options, ScalarsInArrays, SelectOptions};
+///
+/// let mut value: Value = json!({
+///     "tags": ["good boy", { "name": "bernese" }],
+/// });
+/// let options = SelectOptions { scalars_in_arrays: ScalarsInArrays::Keep, ..SelectOptions::default() };
+///
+/// let mut visited = Vec::new();
+/// map_leaf_values_with_options(
+///     value.as_object_mut().unwrap(),
+///     ["tags.name"],
+///     &options,
+///     |key, value| visited.push((key.to_string(), value.clone())),
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     visited,
+///     [
+///         ("tags".to_string(), json!("good boy")),
+///         ("tags.name".to_string(), json!("bernese")),
+///     ]
+/// );
+/// ```
+pub fn map_leaf_values_with_options<'a, S: AsRef<str> + ?Sized + 'a>(
+    value: &mut Map<String, Value>,
+    selectors: impl IntoIterator<Item = &'a S>,
+    options: &SelectOptions,
+    mut mapper: impl FnMut(&str, &mut Value),
+) -> Result<(), LimitError> {
+    check_depth(value, options.max_depth)?;
+    let selectors = collect_selectors(selectors, options)?;
+    map_object(value, &selectors, "", options, &mut mapper);
+    Ok(())
 }
 
-pub fn map_leaf_values_in_object<'a>(
+pub fn map_leaf_values_in_object(
     value: &mut Map<String, Value>,
-    selectors: &[&'a str],
+    selectors: &[&str],
     base_key: &str,
     mapper: &mut impl FnMut(&str, &mut Value),
 ) {
-    for (key, value) in value.iter_mut() {
-        let base_key = if base_key.is_empty() {
-            key.to_string()
-        } else {
-            format!("{}{}{}", base_key, SPLIT_SYMBOL, key)
-        };
+    map_object(
+        value,
+        selectors,
+        base_key,
+        &SelectOptions::default(),
+        mapper,
+    )
+}
 
-        // here if the user only specified `doggo` we need to iterate in all the fields of `doggo`
-        // so we check the contained_in on both side
-        let should_continue = selectors.into_iter().any(|ref selector| {
-            contained_in(selector, &base_key) || contained_in(&base_key, selector)
-        });
+pub fn map_leaf_values_in_array(
+    values: &mut [Value],
+    selectors: &[&str],
+    base_key: &str,
+    mapper: &mut impl FnMut(&str, &mut Value),
+) {
+    let to_map = match Selection::of(base_key, selectors) {
+        Selection::Selected => values
+            .iter_mut()
+            .rev()
+            .map(|value| ToMap::Selected(value, base_key.to_string()))
+            .collect(),
+        _ => values
+            .iter_mut()
+            .rev()
+            .map(|value| ToMap::Element(value, base_key.to_string()))
+            .collect(),
+    };
+    map_values(to_map, selectors, &SelectOptions::default(), mapper)
+}
 
-        if should_continue {
-            match value {
-                Value::Object(object) => {
-                    map_leaf_values_in_object(object, selectors, &base_key, mapper)
-                }
-                Value::Array(array) => {
-                    map_leaf_values_in_array(array, selectors, &base_key, mapper)
-                }
-                value => mapper(&base_key, value),
-            }
-        }
-    }
+/// A value that must be mapped, with its path.
+enum ToMap<'a> {
+    /// The value of a field, mapped depending on how the selectors treat its path.
+    Field(&'a mut Value, String),
+    /// An element of an array that was traversed by a selector.
+    Element(&'a mut Value, String),
+    /// A selected value, all its leaves are mapped.
+    Selected(&'a mut Value, String),
 }
 
-pub fn map_leaf_values_in_array(
-    values: &mut Vec<Value>,
+fn map_object(
+    value: &mut Document,
     selectors: &[&str],
     base_key: &str,
+    options: &SelectOptions,
     mapper: &mut impl FnMut(&str, &mut Value),
 ) {
-    for value in values.iter_mut() {
-        match value {
-            Value::Object(object) => {
-                map_leaf_values_in_object(object, selectors, &base_key, mapper)
-            }
-            Value::Array(array) => map_leaf_values_in_array(array, selectors, &base_key, mapper),
-            value => mapper(&base_key, value),
+    let to_map = value
+        .iter_mut()
+        .rev()
+        .map(|(key, value)| ToMap::Field(value, join_path(base_key, key)))
+        .collect();
+    map_values(to_map, selectors, options, mapper)
+}
+
+fn map_values(
+    mut to_map: Vec<ToMap<'_>>,
+    selectors: &[&str],
+    options: &SelectOptions,
+    mapper: &mut impl FnMut(&str, &mut Value),
+) {
+    // the json can be too deep to recurse, the values are popped from the end of the stack so
+    // they're pushed in reverse to be mapped in the order they appear
+    while let Some(to_visit) = to_map.pop() {
+        match to_visit {
+            ToMap::Field(value, path) => match (Selection::of(&path, selectors), value) {
+                (Selection::Selected, value) => to_map.push(ToMap::Selected(value, path)),
+                (Selection::Traversed, Value::Object(object)) => to_map.extend(
+                    object
+                        .iter_mut()
+                        .rev()
+                        .map(|(key, value)| ToMap::Field(value, join_path(&path, key))),
+                ),
+                (Selection::Traversed, Value::Array(array)) => to_map.extend(
+                    array
+                        .iter_mut()
+                        .rev()
+                        .map(|value| ToMap::Element(value, path.clone())),
+                ),
+                _ => (),
+            },
+            ToMap::Element(value, path) => match value {
+                Value::Object(object) => to_map.extend(
+                    object
+                        .iter_mut()
+                        .rev()
+                        .map(|(key, value)| ToMap::Field(value, join_path(&path, key))),
+                ),
+                Value::Array(array) => to_map.extend(
+                    array
+                        .iter_mut()
+                        .rev()
+                        .map(|value| ToMap::Element(value, path.clone())),
+                ),
+                value if options.scalars_in_arrays == ScalarsInArrays::Keep => mapper(&path, value),
+                _ => (),
+            },
+            ToMap::Selected(value, path) => match value {
+                Value::Object(object) => to_map.extend(
+                    object
+                        .iter_mut()
+                        .rev()
+                        .map(|(key, value)| ToMap::Selected(value, join_path(&path, key))),
+                ),
+                Value::Array(array) => to_map.extend(
+                    array
+                        .iter_mut()
+                        .rev()
+                        .map(|value| ToMap::Selected(value, path.clone())),
+                ),
+                value => mapper(&path, value),
+            },
         }
     }
 }
@@ -140,91 +298,699 @@ pub fn map_leaf_values_in_array(
 ///     })
 /// );
 /// ```
-pub fn select_values<'a>(
+pub fn select_values<'a, S: AsRef<str> + ?Sized + 'a>(
     value: &Map<String, Value>,
-    selectors: impl IntoIterator<Item = &'a str>,
+    selectors: impl IntoIterator<Item = &'a S>,
 ) -> Map<String, Value> {
-    let selectors = selectors.into_iter().collect();
-    create_value(value, selectors)
-}
-
-fn create_value(value: &Document, mut selectors: HashSet<&str>) -> Document {
-    let mut new_value: Document = Map::new();
-
-    for (key, value) in value.iter() {
-        // first we insert all the key at the root level
-        if selectors.contains(key as &str) {
-            new_value.insert(key.to_string(), value.clone());
-            // if the key was simple we can delete it and move to
-            // the next key
-            if is_simple(key) {
-                selectors.remove(key as &str);
-                continue;
+    let selectors: Vec<_> = selectors.into_iter().map(AsRef::as_ref).collect();
+    create_value(
+        value,
+        &selectors,
+        "",
+        &SelectOptions::default(),
+        &mut Budget::unlimited(),
+    )
+    .expect("an unlimited budget can't be exceeded")
+}
+
+/// The options of [`select_values_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectOptions {
+    /// The shape of the returned json.
+    pub shape: OutputShape,
+    /// What to do with the objects and arrays in which nothing was selected.
+    pub placeholder: Placeholder,
+    /// What to do with the scalars of an array traversed by a selector.
+    pub scalars_in_arrays: ScalarsInArrays,
+    /// The maximum number of nested objects and arrays in the json, see [`check_depth`].
+    /// Defaults to 128, the recursion limit of `serde_json`.
+    pub max_depth: usize,
+    /// The maximum number of selectors, unlimited by default.
+    pub max_selectors: Option<usize>,
+    /// The maximum number of values in the selection, every object, array and scalar counts as
+    /// one value. Unlimited by default.
+    pub max_selected_nodes: Option<usize>,
+    /// The maximum size of the selection once serialized, in bytes. The size is the one of the
+    /// nested json serialized by `serde_json` without whitespace, escaped characters included,
+    /// whatever the [`OutputShape`]. Unlimited by default.
+    pub max_output_bytes: Option<usize>,
+}
+
+impl Default for SelectOptions {
+    fn default() -> Self {
+        Self {
+            shape: OutputShape::default(),
+            placeholder: Placeholder::default(),
+            scalars_in_arrays: ScalarsInArrays::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_selectors: None,
+            max_selected_nodes: None,
+            max_output_bytes: None,
+        }
+    }
+}
+
+/// The shape of the json returned by [`select_values_with_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputShape {
+    /// The selected fields keep the structure they had in the original json, like with
+    /// [`select_values`].
+    #[default]
+    Nested,
+    /// Every selected leaf is inserted at the root of the json under its full dotted path,
+    /// the same path [`map_leaf_values`] gives to the mapper.
+    /// When multiple leaves share the same path, or when a leaf was found in an array, the
+    /// path contains an array of all these leaves.
+    /// The [`Placeholder`] is ignored, only the selected leaves are inserted.
+    Flattened,
+}
+
+/// What to do with an object or an array that was traversed by a selector but in which nothing
+/// was selected.
+///
+/// With the following json and the selector `pets.age`:
+/// ```json
+/// {
+///   "pets": [
+///     { "name": "jean" },
+///     { "name": "bob", "age": 4 },
+///     { "name": "michel" }
+///   ]
+/// }
+/// ```
+/// - [`Placeholder::Remove`] returns `{ "pets": [{ "age": 4 }] }`.
+/// - [`Placeholder::Empty`] returns `{ "pets": [{}, { "age": 4 }, {}] }`.
+/// - [`Placeholder::Null`] returns `{ "pets": [null, { "age": 4 }, null] }`.
+///
+/// Keeping the placeholders preserves the length of the arrays, which lets you correlate the
+/// elements of different projections of the same array. To do so, the scalars that
+/// [`ScalarsInArrays::Drop`] would drop are replaced by `null` with [`Placeholder::Empty`] and
+/// [`Placeholder::Null`].
+///
+/// The placeholders are never inserted with [`OutputShape::Flattened`], since the positions
+/// they preserve are lost once flattened.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// The empty objects and arrays are removed.
+    #[default]
+    Remove,
+    /// The empty objects and arrays are kept.
+    Empty,
+    /// The empty objects and arrays are replaced by `null`.
+    Null,
+}
+
+impl Placeholder {
+    /// Returns the value that must be inserted in place of a sub-selection.
+    fn apply(&self, value: Value) -> Option<Value> {
+        let is_empty = match &value {
+            Value::Array(array) => array.is_empty(),
+            Value::Object(object) => object.is_empty(),
+            _ => false,
+        };
+
+        match self {
+            _ if !is_empty => Some(value),
+            Placeholder::Remove => None,
+            Placeholder::Empty => Some(value),
+            Placeholder::Null => Some(Value::Null),
+        }
+    }
+}
+
+/// What to do with the scalars found in an array when a selector goes deeper than the array.
+///
+/// With the following json and the selector `tags.name`:
+/// ```json
+/// {
+///   "tags": ["good boy", { "name": "bernese" }]
+/// }
+/// ```
+/// - [`ScalarsInArrays::Drop`] returns `{ "tags": [{ "name": "bernese" }] }`, unless a
+///   [`Placeholder`] is kept, then it behaves like [`ScalarsInArrays::Null`].
+/// - [`ScalarsInArrays::Keep`] returns `{ "tags": ["good boy", { "name": "bernese" }] }`.
+/// - [`ScalarsInArrays::Null`] returns `{ "tags": [null, { "name": "bernese" }] }`.
+///
+/// With [`map_leaf_values_with_options`] the scalars are only given to the mapper with
+/// [`ScalarsInArrays::Keep`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarsInArrays {
+    /// The scalars are not selected.
+    #[default]
+    Drop,
+    /// The scalars are selected.
+    Keep,
+    /// The scalars are replaced by `null`.
+    Null,
+}
+
+/// Same as [`select_values`] but lets you customize the returned json with [`SelectOptions`].
+/// Returns an error if the json or the selection exceeds one of the limits.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values_with_options, OutputShape, SelectOptions};
+///
+/// let value: Value = json!({
+///     "name": "peanut",
+///     "race": {
+///         "name": "bernese mountain",
+///         "size": "80cm",
+///     },
+///     "toys": [{ "name": "ball" }, { "name": "stick" }],
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// let options = SelectOptions { shape: OutputShape::Flattened, ..SelectOptions::default() };
+/// let res: Value = select_values_with_options(value, ["name", "race", "toys.name"], &options)
+///     .unwrap()
+///     .into();
+/// assert_eq!(
+///     res,
+///     json!({
+///         "name": "peanut",
+///         "race.name": "bernese mountain",
+///         "race.size": "80cm",
+///         "toys.name": ["ball", "stick"],
+///     })
+/// );
+/// ```
+pub fn select_values_with_options<'a, S: AsRef<str> + ?Sized + 'a>(
+    value: &Map<String, Value>,
+    selectors: impl IntoIterator<Item = &'a S>,
+    options: &SelectOptions,
+) -> Result<Map<String, Value>, LimitError> {
+    check_depth(value, options.max_depth)?;
+    let selectors = collect_selectors(selectors, options)?;
+    select_in_object(value, &selectors, options)
+}
+
+/// Selects the values of a json whose depth was already checked.
+fn select_in_object(
+    value: &Document,
+    selectors: &[&str],
+    options: &SelectOptions,
+) -> Result<Document, LimitError> {
+    match options.shape {
+        OutputShape::Nested => {
+            create_value(value, selectors, "", options, &mut Budget::new(options))
+        }
+        OutputShape::Flattened => {
+            // the placeholders only make sense at the position they hold in the nested json
+            let options = SelectOptions {
+                placeholder: Placeholder::Remove,
+                ..*options
+            };
+            let selected =
+                create_value(value, selectors, "", &options, &mut Budget::new(&options))?;
+            Ok(flatten(selected))
+        }
+    }
+}
+
+fn collect_selectors<'a, S: AsRef<str> + ?Sized + 'a>(
+    selectors: impl IntoIterator<Item = &'a S>,
+    options: &SelectOptions,
+) -> Result<Vec<&'a str>, LimitError> {
+    let selectors: Vec<_> = selectors.into_iter().map(AsRef::as_ref).collect();
+    match options.max_selectors {
+        Some(max_selectors) if selectors.len() > max_selectors => {
+            Err(LimitError::MaxSelectors { max_selectors })
+        }
+        _ => Ok(selectors),
+    }
+}
+
+/// Moves all the leaves of a json at its root under their full dotted path.
+fn flatten(value: Document) -> Document {
+    let mut leaves: BTreeMap<String, (bool, Vec<Value>)> = BTreeMap::new();
+
+    // the values are pushed in reverse to be popped in the order they appear
+    let mut to_flatten: Vec<(Value, String, bool)> = value
+        .into_iter()
+        .rev()
+        .map(|(key, value)| (value, key, false))
+        .collect();
+    while let Some((value, key, in_array)) = to_flatten.pop() {
+        match value {
+            Value::Object(object) if !object.is_empty() => to_flatten.extend(
+                object
+                    .into_iter()
+                    .rev()
+                    .map(|(sub_key, value)| (value, join_path(&key, &sub_key), in_array)),
+            ),
+            Value::Array(array) if !array.is_empty() => to_flatten.extend(
+                array
+                    .into_iter()
+                    .rev()
+                    .map(|value| (value, key.clone(), true)),
+            ),
+            value => {
+                let (leaf_in_array, values) = leaves.entry(key).or_default();
+                *leaf_in_array |= in_array;
+                values.push(value);
             }
         }
+    }
+
+    leaves
+        .into_iter()
+        .map(|(key, (in_array, mut values))| {
+            if !in_array && values.len() == 1 {
+                (key, values.pop().unwrap())
+            } else {
+                (key, Value::Array(values))
+            }
+        })
+        .collect()
+}
+
+fn create_value(
+    value: &Document,
+    selectors: &[&str],
+    base_key: &str,
+    options: &SelectOptions,
+    budget: &mut Budget,
+) -> Result<Document, LimitError> {
+    // the objects and arrays being traversed, with their path and the key they must be
+    // inserted under in their parent, the json can be too deep to recurse
+    let root = Partial::Object(value.iter(), Map::new());
+    let mut stack = vec![(root, base_key.to_string(), None)];
+
+    while let Some((partial, path, _)) = stack.last_mut() {
+        let Some((key, value)) = partial.next() else {
+            let (partial, _, key) = stack.pop().unwrap();
+            let value = partial.finish();
+            let Some((parent, _, _)) = stack.last_mut() else {
+                return match value {
+                    Value::Object(object) => Ok(object),
+                    _ => unreachable!("the root is an object"),
+                };
+            };
+            if let Some(value) = options.placeholder.apply(value) {
+                budget.spend(key.map(String::as_str), &value)?;
+                parent.push(key, value);
+            }
+            continue;
+        };
 
-        // we extract all the sub selectors matching the current field
-        // if there was [person.name, person.age] and if we are on the field
-        // `person`. Then we generate the following sub selectors: [name, age].
-        let sub_selectors: HashSet<&str> = selectors
-            .iter()
-            .filter(|s| contained_in(s, key))
-            .filter_map(|s| s.trim_start_matches(key).get(SPLIT_SYMBOL.len_utf8()..))
-            .collect();
-
-        if !sub_selectors.is_empty() {
-            match value {
-                Value::Array(array) => {
-                    let array = create_array(array, &sub_selectors);
-                    if !array.is_empty() {
-                        new_value.insert(key.to_string(), array.into());
+        match key {
+            // a field of a traversed object
+            Some(key) => {
+                let path = join_path(path, key);
+                match (Selection::of(&path, selectors), value) {
+                    (Selection::Selected, value) => {
+                        budget.spend_all(key, value)?;
+                        partial.push(Some(key), clone_value(value));
                     }
-                }
-                Value::Object(object) => {
-                    let object = create_value(object, sub_selectors);
-                    if !object.is_empty() {
-                        new_value.insert(key.to_string(), object.into());
+                    (Selection::Traversed, Value::Object(_) | Value::Array(_)) => {
+                        let child = Partial::of(value).unwrap();
+                        stack.push((child, path, Some(key)));
                     }
+                    _ => (),
                 }
-                _ => (),
             }
+            // an element of a traversed array
+            None => match Partial::of(value) {
+                Some(child) => {
+                    let path = path.clone();
+                    stack.push((child, path, None));
+                }
+                None => {
+                    let value = match (options.scalars_in_arrays, options.placeholder) {
+                        (ScalarsInArrays::Drop, Placeholder::Remove) => continue,
+                        (ScalarsInArrays::Keep, _) => value.clone(),
+                        // a dropped scalar leaves a placeholder to preserve the length of the array
+                        (ScalarsInArrays::Drop | ScalarsInArrays::Null, _) => Value::Null,
+                    };
+                    budget.spend(None, &value)?;
+                    partial.push(None, value);
+                }
+            },
         }
     }
 
-    new_value
+    unreachable!("the root is returned when it's finished")
 }
 
-fn create_array(array: &Vec<Value>, selectors: &HashSet<&str>) -> Vec<Value> {
-    let mut res = Vec::new();
+/// Returns all the values reached by a selector, in the order they appear in the json.
+/// Arrays are transparent: they're traversed on the way to the selected values, and when a
+/// selected value is itself an array its elements are returned instead of the array.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::get_values;
+///
+/// let value: Value = json!({
+///     "doggos.name": "jean",
+///     "doggos": [
+///         { "name": "bob" },
+///         { "name": ["marc", "patou"] },
+///         { "race": { "name": "golden retriever" } },
+///     ],
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// assert_eq!(
+///     get_values(value, "doggos.name"),
+///     [&json!("bob"), &json!("marc"), &json!("patou"), &json!("jean")],
+/// );
+/// assert_eq!(
+///     get_values(value, "doggos.race"),
+///     [&json!({ "name": "golden retriever" })],
+/// );
+/// ```
+pub fn get_values(value: &Map<String, Value>, selector: impl AsRef<str>) -> Vec<&Value> {
+    let mut values = Vec::new();
+    for route in find_routes(value, selector.as_ref()) {
+        if let Some(value) = follow(value, &route) {
+            push_flattened_value(value, &mut values);
+        }
+    }
+    values
+}
 
-    for value in array {
+fn push_flattened_value<'a>(value: &'a Value, values: &mut Vec<&'a Value>) {
+    // the elements are pushed in reverse to be popped in the order they appear
+    let mut to_push = vec![value];
+    while let Some(value) = to_push.pop() {
         match value {
-            Value::Array(array) => {
-                let array = create_array(array, selectors);
-                if !array.is_empty() {
-                    res.push(array.into());
-                }
-            }
-            Value::Object(object) => {
-                let object = create_value(object, selectors.clone());
-                if !object.is_empty() {
-                    res.push(object.into());
-                }
+            Value::Array(array) => to_push.extend(array.iter().rev()),
+            value => values.push(value),
+        }
+    }
+}
+
+/// When a selector matches multiple values, decides which one is returned by [`get_first`] and
+/// [`get_first_mut`].
+///
+/// With the following json and the selector `pet.dog.name`:
+/// ```json
+/// {
+///   "pet.dog": { "name": "bob" },
+///   "pet": {
+///     "dog.name": "michel",
+///     "dog": { "name": "milan" }
+///   }
+/// }
+/// ```
+/// - [`Precedence::FlattenedFirst`] returns `"bob"`.
+/// - [`Precedence::NestedFirst`] returns `"milan"`.
+/// - [`Precedence::ShallowestFirst`] returns `"bob"`, since `"bob"` and `"michel"` are both
+///   two keys deep, the tie is broken as with [`Precedence::FlattenedFirst`].
+///
+/// With [`Precedence::FlattenedFirst`] and [`Precedence::NestedFirst`], inside an array the
+/// first element containing a match always wins. With [`Precedence::ShallowestFirst`] the
+/// depth is compared first, in `{ "doggos": [{ "a": { "b": 1 } }, { "a.b": 2 }] }` the
+/// selector `doggos.a.b` returns `2`, and the first element only wins between values
+/// reached by following the same number of keys.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Precedence {
+    /// At each level, the longest key matching the selector is tried first.
+    #[default]
+    FlattenedFirst,
+    /// At each level, the shortest key matching the selector is tried first.
+    NestedFirst,
+    /// The value reached by following the fewest keys wins.
+    ShallowestFirst,
+}
+
+impl Precedence {
+    fn compare(&self, left: &Route, right: &Route) -> Ordering {
+        let depth = |route: &Route| {
+            route
+                .iter()
+                .filter(|step| matches!(step, Step::Key(_)))
+                .count()
+        };
+
+        match self {
+            Precedence::FlattenedFirst => compare_routes(left, right, true),
+            Precedence::NestedFirst => compare_routes(left, right, false),
+            Precedence::ShallowestFirst => depth(left)
+                .cmp(&depth(right))
+                .then_with(|| compare_routes(left, right, true)),
+        }
+    }
+}
+
+/// Compares two routes on the first step where they diverge.
+fn compare_routes(left: &Route, right: &Route, flattened_first: bool) -> Ordering {
+    let diverging = left.iter().zip(right).find(|(left, right)| left != right);
+    match diverging {
+        Some((Step::Key(left), Step::Key(right))) if flattened_first => {
+            right.len().cmp(&left.len())
+        }
+        Some((Step::Key(left), Step::Key(right))) => left.len().cmp(&right.len()),
+        Some((Step::Index(left), Step::Index(right))) => left.cmp(right),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Returns the value matched by a selector, if multiple values match, the [`Precedence`] decides
+/// which one is returned.
+/// Contrary to [`get_values`], the selected value is returned as-is even if it's an array, and
+/// only the fields whose path is exactly the selector match, a flattened key nested under the
+/// selector like `dog.name` for the selector `dog` doesn't.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{get_first, Precedence};
+///
+/// let value: Value = json!({
+///     "dog.name": "jean",
+///     "dog": {
+///         "name": "bob",
+///     },
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// assert_eq!(get_first(value, "dog.name", Precedence::FlattenedFirst), Some(&json!("jean")));
+/// assert_eq!(get_first(value, "dog.name", Precedence::NestedFirst), Some(&json!("bob")));
+/// assert_eq!(get_first(value, "dog.age", Precedence::NestedFirst), None);
+/// ```
+pub fn get_first(
+    value: &Map<String, Value>,
+    selector: impl AsRef<str>,
+    precedence: Precedence,
+) -> Option<&Value> {
+    let route = find_first_route(value, selector.as_ref(), precedence)?;
+    follow(value, &route)
+}
+
+/// Same as [`get_first`] but returns a mutable reference to the value.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{get_first_mut, Precedence};
+///
+/// let mut value: Value = json!({
+///     "dog.name": "jean",
+///     "dog": {
+///         "name": "bob",
+///     },
+/// });
+///
+/// let name = get_first_mut(value.as_object_mut().unwrap(), "dog.name", Precedence::NestedFirst);
+/// *name.unwrap() = json!("patou");
+/// assert_eq!(value, json!({ "dog.name": "jean", "dog": { "name": "patou" } }));
+/// ```
+pub fn get_first_mut(
+    value: &mut Map<String, Value>,
+    selector: impl AsRef<str>,
+    precedence: Precedence,
+) -> Option<&mut Value> {
+    let route = find_first_route(value, selector.as_ref(), precedence)?;
+    follow_mut(value, &route)
+}
+
+fn find_first_route(value: &Document, selector: &str, precedence: Precedence) -> Option<Route> {
+    find_exact_routes(value, selector)
+        .into_iter()
+        .min_by(|left, right| precedence.compare(left, right))
+}
+
+/// How [`set_value`] creates a field that doesn't exist yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingParents {
+    /// The missing parents are created as nested objects, setting `dog.race.name` in an
+    /// empty object creates `{ "dog": { "race": { "name": … } } }`.
+    #[default]
+    Nested,
+    /// The missing parents are kept in the key, setting `dog.race.name` in an empty object
+    /// creates `{ "dog.race.name": … }`.
+    Flattened,
+}
+
+/// Permissively sets a value in a json.
+/// If one or more fields have exactly the selector as path, all of them are replaced by the
+/// new value, a flattened key nested under the selector is left untouched.
+/// Otherwise the value is inserted in the deepest object already matching the beginning of
+/// the selector, and the missing parents are created following [`MissingParents`].
+/// An existing field that isn't an object is never overwritten to create a parent, the rest
+/// of the selector is inserted as a flattened key next to it instead.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{set_value, MissingParents};
+///
+/// let mut value: Value = json!({
+///     "dog.name": "jean",
+///     "dog": {
+///         "name": "bob",
+///     },
+/// });
+/// let object = value.as_object_mut().unwrap();
+///
+/// set_value(object, "dog.name", json!("patou"), MissingParents::Nested);
+/// set_value(object, "dog.race.name", json!("bernese mountain"), MissingParents::Nested);
+/// set_value(object, "dog.race.size", json!("80cm"), MissingParents::Flattened);
+/// set_value(object, "cat.name", json!("michel"), MissingParents::Flattened);
+///
+/// assert_eq!(
+///     value,
+///     json!({
+///         "dog.name": "patou",
+///         "dog": {
+///             "name": "patou",
+///             "race": {
+///                 "name": "bernese mountain",
+///                 "size": "80cm",
+///             },
+///         },
+///         "cat.name": "michel",
+///     })
+/// );
+/// ```
+pub fn set_value(
+    value: &mut Map<String, Value>,
+    selector: impl AsRef<str>,
+    new_value: Value,
+    missing_parents: MissingParents,
+) {
+    let selector = selector.as_ref();
+    let mut routes = find_exact_routes(value, selector);
+    let Some(last) = routes.pop() else {
+        return insert_value(value, selector, new_value, missing_parents);
+    };
+
+    // the values are cloned and dropped without recursion since they can be deeply nested
+    for route in routes {
+        if let Some(value) = follow_mut(value, &route) {
+            drop_value(mem::replace(value, clone_value(&new_value)));
+        }
+    }
+    if let Some(value) = follow_mut(value, &last) {
+        drop_value(mem::replace(value, new_value));
+    }
+}
+
+fn insert_value(
+    mut value: &mut Document,
+    mut selector: &str,
+    new_value: Value,
+    missing_parents: MissingParents,
+) {
+    // we look for the most specific existing object we can insert the value in
+    while let Some((key, sub_selector)) = value
+        .iter()
+        .filter(|(_, value)| value.is_object())
+        .filter_map(|(key, _)| {
+            let sub_selector = selector.strip_prefix(key.as_str())?;
+            Some((key, sub_selector.strip_prefix(SPLIT_SYMBOL)?))
+        })
+        .max_by_key(|(key, _)| key.len())
+    {
+        let key = key.clone();
+        selector = sub_selector;
+        value = match value.get_mut(&key) {
+            Some(Value::Object(object)) => object,
+            _ => unreachable!("the parent is an object"),
+        };
+    }
+
+    if missing_parents == MissingParents::Nested {
+        while let Some((key, sub_selector)) = selector.split_once(SPLIT_SYMBOL) {
+            if value.contains_key(key) {
+                break;
             }
-            _ => (),
+            value = match value
+                .entry(key)
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                Value::Object(object) => object,
+                _ => unreachable!("the parent was just created"),
+            };
+            selector = sub_selector;
         }
     }
 
-    res
+    value.insert(selector.to_string(), new_value);
 }
 
-fn is_simple(key: impl AsRef<str>) -> bool {
-    !key.as_ref().contains(SPLIT_SYMBOL)
+/// Lists every dotted path a selector could match in a json.
+/// The elements of an array are transparent, they share the path of the array itself.
+/// If `leaves_only` is `true` only the paths leading to a value that can't be traversed
+/// further are returned, otherwise all the intermediate objects are returned as well.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::field_paths;
+///
+/// let value: Value = json!({
+///     "name": "peanut",
+///     "race.name": "bernese mountain",
+///     "race": {
+///         "size": "80cm",
+///     },
+///     "toys": [{ "name": "ball" }, "stick"],
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// let paths = field_paths(value, false);
+/// assert_eq!(
+///     paths.into_iter().collect::<Vec<_>>(),
+///     ["name", "race", "race.name", "race.size", "toys", "toys.name"],
+/// );
+///
+/// let paths = field_paths(value, true);
+/// assert_eq!(
+///     paths.into_iter().collect::<Vec<_>>(),
+///     ["name", "race.name", "race.size", "toys", "toys.name"],
+/// );
+/// ```
+pub fn field_paths(value: &Map<String, Value>, leaves_only: bool) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+
+    // the values to visit, with their path and whether they're the element of an array
+    let mut to_visit: Vec<(&Value, String, bool)> = value
+        .iter()
+        .map(|(key, value)| (value, key.clone(), false))
+        .collect();
+    while let Some((value, path, in_array)) = to_visit.pop() {
+        let is_empty = match value {
+            Value::Object(object) => {
+                to_visit.extend(
+                    object
+                        .iter()
+                        .map(|(key, value)| (value, join_path(&path, key), false)),
+                );
+                object.is_empty()
+            }
+            Value::Array(array) => {
+                to_visit.extend(array.iter().map(|value| (value, path.clone(), true)));
+                array.is_empty()
+            }
+            _ => true,
+        };
+
+        // an element of an array shares the path of the array, which is already listed
+        if is_empty || (!leaves_only && !in_array) {
+            paths.insert(path);
+        }
+    }
+
+    paths
 }
 
 #[cfg(test)]
 mod tests {
     use big_s::S;
+    use serde_json::json;
 
     use super::*;
 
@@ -771,4 +1537,971 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn list_field_paths() {
+        let value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                    "toys": [],
+                }
+            },
+            "doggos": [
+                {
+                    "name": "marc",
+                    "collar": {},
+                },
+                [
+                    {
+                        "age": 4,
+                    },
+                    "patou",
+                ],
+            ],
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        let paths: Vec<_> = field_paths(value, false).into_iter().collect();
+        assert_eq!(
+            paths,
+            vec![
+                "doggos",
+                "doggos.age",
+                "doggos.collar",
+                "doggos.name",
+                "pet",
+                "pet.dog",
+                "pet.dog.name",
+                "pet.dog.toys",
+            ]
+        );
+
+        let paths: Vec<_> = field_paths(value, true).into_iter().collect();
+        assert_eq!(
+            paths,
+            vec![
+                "doggos",
+                "doggos.age",
+                "doggos.collar",
+                "doggos.name",
+                "pet.dog.name",
+                "pet.dog.toys",
+            ]
+        );
+
+        assert!(field_paths(&Map::new(), false).is_empty());
+    }
+
+    #[test]
+    fn get_all_values() {
+        let value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                }
+            },
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        assert_eq!(
+            get_values(value, "pet.dog.name"),
+            vec![
+                &json!("milan"),
+                &json!("michel"),
+                &json!("bob"),
+                &json!("jean")
+            ]
+        );
+        // the flattened keys nested under the selector are selected as well
+        assert_eq!(
+            get_values(value, "pet.dog"),
+            vec![
+                &json!({ "name": "milan" }),
+                &json!("michel"),
+                &json!({ "name": "bob" }),
+                &json!("jean")
+            ]
+        );
+        assert!(get_values(value, "pet.cat").is_empty());
+        assert!(get_values(value, "pet.dog.name.first").is_empty());
+
+        let value: Value = json!({
+            "doggos": [
+                {
+                    "jean": {
+                        "age": 8,
+                        "toys": ["ball", ["stick", { "name": "bone" }]],
+                    }
+                },
+                [
+                    {
+                        "jean": {
+                            "age": 9,
+                        }
+                    },
+                    "marc",
+                ],
+                "bob",
+            ]
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        assert_eq!(
+            get_values(value, "doggos.jean.age"),
+            vec![&json!(8), &json!(9)]
+        );
+        assert_eq!(
+            get_values(value, "doggos.jean.toys"),
+            vec![&json!("ball"), &json!("stick"), &json!({ "name": "bone" })]
+        );
+        assert_eq!(
+            get_values(value, "doggos.jean.toys.name"),
+            vec![&json!("bone")]
+        );
+        assert_eq!(get_values(value, "doggos").len(), 4);
+    }
+
+    #[test]
+    fn get_first_with_precedence() {
+        let value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                }
+            },
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        let first = |selector, precedence| get_first(value, selector, precedence);
+        assert_eq!(
+            first("pet.dog.name", Precedence::FlattenedFirst),
+            Some(&json!("jean"))
+        );
+        assert_eq!(
+            first("pet.dog.name", Precedence::NestedFirst),
+            Some(&json!("milan"))
+        );
+        assert_eq!(
+            first("pet.dog.name", Precedence::ShallowestFirst),
+            Some(&json!("jean"))
+        );
+        assert_eq!(
+            first("pet.dog", Precedence::FlattenedFirst),
+            Some(&json!({ "name": "bob" }))
+        );
+        assert_eq!(
+            first("pet.dog", Precedence::NestedFirst),
+            Some(&json!({ "name": "milan" }))
+        );
+        assert_eq!(first("pet.cat", Precedence::FlattenedFirst), None);
+
+        // the flattened key is found first but the nested one is shallower
+        let value: Value = json!({
+            "a.b": {
+                "c": {
+                    "d": "deep",
+                },
+            },
+            "a": {
+                "b.c.d": "shallow",
+            },
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        assert_eq!(
+            get_first(value, "a.b.c.d", Precedence::FlattenedFirst),
+            Some(&json!("deep"))
+        );
+        assert_eq!(
+            get_first(value, "a.b.c.d", Precedence::NestedFirst),
+            Some(&json!("shallow"))
+        );
+        assert_eq!(
+            get_first(value, "a.b.c.d", Precedence::ShallowestFirst),
+            Some(&json!("shallow"))
+        );
+
+        // the depth is compared before the position in the array
+        let value: Value = json!({ "doggos": [{ "a": { "b": 1 } }, { "a.b": 2 }] });
+        let value: &Document = value.as_object().unwrap();
+
+        assert_eq!(
+            get_first(value, "doggos.a.b", Precedence::FlattenedFirst),
+            Some(&json!(1))
+        );
+        assert_eq!(
+            get_first(value, "doggos.a.b", Precedence::NestedFirst),
+            Some(&json!(1))
+        );
+        assert_eq!(
+            get_first(value, "doggos.a.b", Precedence::ShallowestFirst),
+            Some(&json!(2))
+        );
+
+        let mut value: Value = json!({
+            "doggos": [
+                {
+                    "age": 8,
+                },
+                [
+                    {
+                        "name": "marc",
+                    },
+                    {
+                        "name.first": "jean",
+                        "name": ["bob"],
+                    },
+                ],
+            ]
+        });
+        let object = value.as_object_mut().unwrap();
+
+        assert_eq!(
+            get_first(object, "doggos.name", Precedence::FlattenedFirst),
+            Some(&json!("marc"))
+        );
+        assert_eq!(
+            get_first(object, "doggos.name.first", Precedence::NestedFirst),
+            Some(&json!("jean"))
+        );
+
+        *get_first_mut(object, "doggos.name", Precedence::NestedFirst).unwrap() = json!("patou");
+        get_first_mut(object, "doggos.age", Precedence::NestedFirst)
+            .unwrap()
+            .take();
+        assert!(get_first_mut(object, "doggos.race", Precedence::NestedFirst).is_none());
+        assert_eq!(
+            value,
+            json!({
+                "doggos": [
+                    {
+                        "age": null,
+                    },
+                    [
+                        {
+                            "name": "patou",
+                        },
+                        {
+                            "name.first": "jean",
+                            "name": ["bob"],
+                        },
+                    ],
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn set_value_everywhere() {
+        let mut value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                }
+            },
+            "doggos": [
+                { "name": "marc" },
+                { "age": 4 },
+                [{ "name": "patou" }],
+            ],
+        });
+        let object = value.as_object_mut().unwrap();
+
+        set_value(
+            object,
+            "pet.dog.name",
+            json!("kefir"),
+            MissingParents::Nested,
+        );
+        set_value(
+            object,
+            "doggos.name",
+            json!("kefir"),
+            MissingParents::Nested,
+        );
+        assert_eq!(
+            value,
+            json!({
+                "pet.dog.name": "kefir",
+                "pet.dog": {
+                    "name": "kefir",
+                },
+                "pet": {
+                    "dog.name": "kefir",
+                    "dog": {
+                        "name": "kefir",
+                    }
+                },
+                "doggos": [
+                    { "name": "kefir" },
+                    { "age": 4 },
+                    [{ "name": "kefir" }],
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn set_missing_value() {
+        let mut value: Value = json!({
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "cat": "michel",
+            },
+            "doggos": [],
+        });
+        let object = value.as_object_mut().unwrap();
+
+        set_value(object, "pet.dog.age", json!(4), MissingParents::Nested);
+        set_value(
+            object,
+            "pet.bird.name",
+            json!("ruth"),
+            MissingParents::Nested,
+        );
+        set_value(
+            object,
+            "pet.fish.name",
+            json!("nemo"),
+            MissingParents::Flattened,
+        );
+        set_value(object, "pet.cat.name", json!("tom"), MissingParents::Nested);
+        set_value(object, "doggos.name", json!("marc"), MissingParents::Nested);
+        set_value(
+            object,
+            "horse.race.name",
+            json!("shire"),
+            MissingParents::Nested,
+        );
+        assert_eq!(
+            value,
+            json!({
+                "pet.dog": {
+                    "name": "bob",
+                    "age": 4,
+                },
+                "pet": {
+                    "cat": "michel",
+                    "cat.name": "tom",
+                    "bird": {
+                        "name": "ruth",
+                    },
+                    "fish.name": "nemo",
+                },
+                "doggos": [],
+                "doggos.name": "marc",
+                "horse": {
+                    "race": {
+                        "name": "shire",
+                    },
+                },
+            })
+        );
+
+        // the empty key disappears from the paths but can still be a parent
+        let mut value: Value = json!({ "": { "x": {} } });
+        let object = value.as_object_mut().unwrap();
+        set_value(object, ".x", json!(1), MissingParents::Nested);
+        set_value(object, "x", json!(2), MissingParents::Nested);
+        assert_eq!(value, json!({ "": { "x": 1 }, "x": 2 }));
+
+        let mut value = Map::new();
+        set_value(&mut value, "dog", json!("bob"), MissingParents::Nested);
+        set_value(
+            &mut value,
+            "cat.name",
+            json!("tom"),
+            MissingParents::Flattened,
+        );
+        assert_eq!(
+            Value::from(value),
+            json!({
+                "dog": "bob",
+                "cat.name": "tom",
+            })
+        );
+    }
+
+    #[test]
+    fn set_value_next_to_flattened_child() {
+        // the flattened key nested under the selector isn't the selected field
+        let mut value: Value = json!({ "pet.dog.name": "jean" });
+        let object = value.as_object_mut().unwrap();
+
+        set_value(
+            object,
+            "pet.dog",
+            json!({ "age": 3 }),
+            MissingParents::Nested,
+        );
+        assert_eq!(
+            value,
+            json!({
+                "pet.dog.name": "jean",
+                "pet": {
+                    "dog": { "age": 3 },
+                },
+            })
+        );
+
+        let mut value: Value = json!({ "title.en": "Hello", "title.fr": "Bonjour" });
+        let object = value.as_object_mut().unwrap();
+        assert_eq!(get_first(object, "title", Precedence::NestedFirst), None);
+        assert!(get_first_mut(object, "title", Precedence::FlattenedFirst).is_none());
+        assert_eq!(get_values(object, "title").len(), 2);
+
+        set_value(object, "title", json!("Hi"), MissingParents::Flattened);
+        assert_eq!(
+            value,
+            json!({ "title.en": "Hello", "title.fr": "Bonjour", "title": "Hi" })
+        );
+    }
+
+    #[test]
+    fn flattened_output() {
+        let value: Value = json!({
+            "name": "peanut",
+            "race.name": "bernese mountain",
+            "race": {
+                "name": "bernese",
+                "size": "80cm",
+                "colors": {},
+            },
+            "tags": ["good boy"],
+            "doggos": [
+                {
+                    "jean": {
+                        "age": 8,
+                        "toys": [],
+                    }
+                },
+                [
+                    {
+                        "jean": {
+                            "age": 9,
+                        }
+                    },
+                ],
+            ],
+        });
+        let value: &Document = value.as_object().unwrap();
+        let flattened = SelectOptions {
+            shape: OutputShape::Flattened,
+            ..SelectOptions::default()
+        };
+
+        let res: Value = select_values_with_options(
+            value,
+            ["name", "race", "race.name", "tags", "doggos.jean"],
+            &flattened,
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "name": "peanut",
+                "race.name": ["bernese", "bernese mountain"],
+                "race.size": "80cm",
+                "race.colors": {},
+                "tags": ["good boy"],
+                "doggos.jean.age": [8, 9],
+                "doggos.jean.toys": [[]],
+            })
+        );
+
+        let res =
+            select_values_with_options(value, ["name", "race"], &SelectOptions::default()).unwrap();
+        assert_eq!(res, select_values(value, ["name", "race"]));
+
+        let res = select_values_with_options(value, ["cat"], &flattened).unwrap();
+        assert!(res.is_empty());
+
+        // the placeholders aren't inserted as leaves
+        let value: Value = json!({ "a": [{ "x": 1 }, { "b": 2 }] });
+        for placeholder in [Placeholder::Remove, Placeholder::Empty, Placeholder::Null] {
+            let options = SelectOptions {
+                placeholder,
+                ..flattened
+            };
+            let res: Value =
+                select_values_with_options(value.as_object().unwrap(), ["a.b"], &options)
+                    .unwrap()
+                    .into();
+            assert_eq!(res, json!({ "a.b": [2] }));
+        }
+    }
+
+    #[test]
+    fn keep_placeholders() {
+        let value: Value = json!({
+            "name": "peanut",
+            "race": {
+                "name": "bernese mountain",
+            },
+            "pets": [
+                { "name": "jean" },
+                { "name": "bob", "age": 4 },
+                [{ "name": "michel" }, { "age": 2 }],
+                [],
+                "patou",
+            ],
+        });
+        let value: &Document = value.as_object().unwrap();
+        let options = |placeholder| SelectOptions {
+            placeholder,
+            ..SelectOptions::default()
+        };
+
+        let res: Value = select_values_with_options(
+            value,
+            ["pets.age", "race.size"],
+            &options(Placeholder::Remove),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "pets": [
+                    { "age": 4 },
+                    [{ "age": 2 }],
+                ],
+            })
+        );
+
+        let res: Value = select_values_with_options(
+            value,
+            ["pets.age", "race.size"],
+            &options(Placeholder::Empty),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "race": {},
+                "pets": [
+                    {},
+                    { "age": 4 },
+                    [{}, { "age": 2 }],
+                    [],
+                    null,
+                ],
+            })
+        );
+
+        let res: Value = select_values_with_options(
+            value,
+            ["pets.age", "race.size"],
+            &options(Placeholder::Null),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "race": null,
+                "pets": [
+                    null,
+                    { "age": 4 },
+                    [null, { "age": 2 }],
+                    null,
+                    null,
+                ],
+            })
+        );
+
+        // the elements keep their position in the array
+        let pets: Value = json!({ "pets": [{ "name": "jean" }, "patou", { "age": 4 }] });
+        for (placeholder, expected) in [
+            (
+                Placeholder::Empty,
+                json!({ "pets": [{}, null, { "age": 4 }] }),
+            ),
+            (
+                Placeholder::Null,
+                json!({ "pets": [null, null, { "age": 4 }] }),
+            ),
+        ] {
+            let res: Value = select_values_with_options(
+                pets.as_object().unwrap(),
+                ["pets.age"],
+                &options(placeholder),
+            )
+            .unwrap()
+            .into();
+            assert_eq!(res, expected);
+        }
+
+        // the placeholders never replace a selected value
+        let res: Value =
+            select_values_with_options(value, ["race", "race.size"], &options(Placeholder::Null))
+                .unwrap()
+                .into();
+        assert_eq!(
+            res,
+            json!({
+                "race": {
+                    "name": "bernese mountain",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn flattened_key_with_sub_selector() {
+        let value: Value = json!({
+            "pet.dog": {
+                "name": "bob",
+                "age": 4,
+            },
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        let res: Value = select_values(value, ["pet.dog", "pet.dog.name"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "pet.dog": {
+                    "name": "bob",
+                    "age": 4,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn scalars_in_arrays() {
+        let value: Value = json!({
+            "tags": ["good boy", { "name": "bernese" }, [12, { "name": "golden" }]],
+            "doggos": ["marc", { "name": "bob" }],
+        });
+        let value: &Document = value.as_object().unwrap();
+        let options = |scalars_in_arrays| SelectOptions {
+            scalars_in_arrays,
+            ..SelectOptions::default()
+        };
+
+        let res: Value = select_values_with_options(
+            value,
+            ["tags.name", "doggos"],
+            &options(ScalarsInArrays::Drop),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "tags": [{ "name": "bernese" }, [{ "name": "golden" }]],
+                "doggos": ["marc", { "name": "bob" }],
+            })
+        );
+
+        let res: Value = select_values_with_options(
+            value,
+            ["tags.name", "doggos"],
+            &options(ScalarsInArrays::Keep),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "tags": ["good boy", { "name": "bernese" }, [12, { "name": "golden" }]],
+                "doggos": ["marc", { "name": "bob" }],
+            })
+        );
+
+        let res: Value = select_values_with_options(
+            value,
+            ["tags.name", "doggos"],
+            &options(ScalarsInArrays::Null),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "tags": [null, { "name": "bernese" }, [null, { "name": "golden" }]],
+                "doggos": ["marc", { "name": "bob" }],
+            })
+        );
+
+        for (policy, expected) in [
+            (
+                ScalarsInArrays::Drop,
+                vec!["doggos", "doggos.name", "tags.name", "tags.name"],
+            ),
+            (
+                ScalarsInArrays::Keep,
+                vec![
+                    "doggos",
+                    "doggos.name",
+                    "tags",
+                    "tags.name",
+                    "tags",
+                    "tags.name",
+                ],
+            ),
+            (
+                ScalarsInArrays::Null,
+                vec!["doggos", "doggos.name", "tags.name", "tags.name"],
+            ),
+        ] {
+            let mut value = value.clone();
+            let mut visited = Vec::new();
+            map_leaf_values_with_options(
+                &mut value,
+                ["tags.name", "doggos"],
+                &options(policy),
+                |key, _| visited.push(key.to_string()),
+            )
+            .unwrap();
+            assert_eq!(visited, expected);
+        }
+    }
+
+    #[test]
+    fn same_rules_for_select_and_map() {
+        let mut value: Value = json!({
+            "dog.name": "jean",
+            "dog": {
+                "name": "bob",
+                "age": 8,
+            },
+            "cat": "michel",
+            "tags": ["good boy", { "name": "bernese" }],
+        });
+
+        // `dog` selects the fields of `dog` whether they are nested or flattened
+        let res: Value = select_values(value.as_object().unwrap(), ["dog", "cat.name"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "dog.name": "jean",
+                "dog": {
+                    "name": "bob",
+                    "age": 8,
+                },
+            })
+        );
+
+        // `cat.name` doesn't select the scalar `cat`, neither does `tags.name` for `good boy`
+        let mut visited = Vec::new();
+        map_leaf_values(
+            value.as_object_mut().unwrap(),
+            ["dog", "cat.name", "tags.name"],
+            |key, _| visited.push(key.to_string()),
+        );
+        assert_eq!(visited, ["dog.age", "dog.name", "dog.name", "tags.name"]);
+    }
+
+    #[test]
+    fn deep_nesting() {
+        // found by the `deep_nesting` fuzz target, selecting this json used to overflow the stack
+        let depth = 20_000;
+        let mut value = Value::Null;
+        for _ in 0..depth {
+            value = Value::Object(Map::from_iter([(S("a"), value)]));
+        }
+        let mut value = Map::from_iter([(S("a"), value)]);
+        let selector = vec!["a"; depth + 1].join(".");
+        let options = SelectOptions::default();
+
+        assert_eq!(
+            select_values_with_options(&value, [&selector], &options),
+            Err(LimitError::MaxDepth { max_depth: 128 })
+        );
+        assert_eq!(
+            map_leaf_values_with_options(&mut value, [&selector], &options, |_, _| ()),
+            Err(LimitError::MaxDepth { max_depth: 128 })
+        );
+
+        let options = SelectOptions {
+            max_depth: depth + 1,
+            ..SelectOptions::default()
+        };
+        assert_eq!(check_depth(&value, depth + 1), Ok(()));
+        assert_eq!(
+            check_depth(&value, depth),
+            Err(LimitError::MaxDepth { max_depth: depth })
+        );
+        assert!(map_leaf_values_with_options(&mut value, ["b"], &options, |_, _| ()).is_ok());
+
+        // the json must be dropped without recursion as well
+        drop_value(Value::Object(value));
+    }
+
+    #[test]
+    fn deep_nesting_without_limits() {
+        // every function traverses the json without recursion, so even the functions without
+        // options can't overflow the stack
+        let depth = 10_000;
+        let mut value = Value::Null;
+        for _ in 0..depth {
+            value = Value::Array(vec![Value::Object(Map::from_iter([(S("a"), value)]))]);
+        }
+        let mut value = Map::from_iter([(S("a"), value)]);
+        let selector = vec!["a"; depth + 1].join(".");
+        let depth = 2 * depth + 1;
+
+        let res = select_values(&value, [&selector]);
+        assert_eq!(check_depth(&res, depth), Ok(()));
+        assert!(check_depth(&res, depth - 1).is_err());
+        drop_value(Value::Object(res));
+        let res = select_values(&value, ["a"]);
+        assert_eq!(check_depth(&res, depth), Ok(()));
+        drop_value(Value::Object(res));
+        let (res, report) = select_values_with_report(&value, [&selector]);
+        assert_eq!(report.matched[&selector].len(), 1);
+        drop_value(Value::Object(res));
+
+        let mut mapped = 0;
+        map_leaf_values(&mut value, ["a"], |_, _| mapped += 1);
+        map_leaf_values_in_object(&mut value, &["a"], "", &mut |_, _| mapped += 1);
+        let Some(Value::Array(array)) = value.get_mut("a") else {
+            unreachable!()
+        };
+        map_leaf_values_in_array(array, &["a"], "a", &mut |_, _| mapped += 1);
+        assert_eq!(mapped, 3);
+
+        assert_eq!(get_values(&value, &selector), [&Value::Null]);
+        assert_eq!(
+            get_first(&value, &selector, Precedence::NestedFirst),
+            Some(&Value::Null)
+        );
+        *get_first_mut(&mut value, &selector, Precedence::NestedFirst).unwrap() = json!(1);
+        assert_eq!(explain(&value, &selector)[0].value, &json!(1));
+        assert_eq!(field_paths(&value, true).len(), 1);
+
+        let mut distribution = FieldDistribution::new();
+        distribution.add(&value);
+        assert_eq!(distribution.get(&selector), 1);
+
+        let projection = Projection::parse(["a as b"]).unwrap();
+        let mut res = projection.apply(&value);
+        drop_value(res.remove("b").unwrap());
+
+        let copy = select_values(&value, ["a"]);
+        set_value(&mut value, &selector, json!(2), MissingParents::Nested);
+        set_value(&mut value, "a", Value::Object(copy), MissingParents::Nested);
+        assert_eq!(get_values(&value, format!("a.{selector}")), [&json!(1)]);
+        drop_value(Value::Object(value));
+    }
+
+    #[test]
+    fn selection_limits() {
+        let value: Value = json!({
+            "name": "peanut",
+            "race": {
+                "name": "bernese",
+                "size": "80cm",
+            },
+            "toys": [{ "name": "ball" }, "stick"],
+        });
+        let mut value: Document = value.as_object().unwrap().clone();
+        let options = |max_selectors, max_selected_nodes, max_output_bytes| SelectOptions {
+            max_selectors,
+            max_selected_nodes,
+            max_output_bytes,
+            ..SelectOptions::default()
+        };
+
+        assert_eq!(
+            select_values_with_options(&value, ["name", "race"], &options(Some(1), None, None)),
+            Err(LimitError::MaxSelectors { max_selectors: 1 })
+        );
+        assert_eq!(
+            map_leaf_values_with_options(
+                &mut value,
+                ["name", "race"],
+                &options(Some(1), None, None),
+                |_, _| unreachable!()
+            ),
+            Err(LimitError::MaxSelectors { max_selectors: 1 })
+        );
+        assert!(select_values_with_options(
+            &value,
+            ["name", "race"],
+            &options(Some(2), None, None)
+        )
+        .is_ok());
+
+        // `race`, `race.name` and `race.size`
+        assert!(
+            select_values_with_options(&value, ["race"], &options(None, Some(3), None)).is_ok()
+        );
+        assert_eq!(
+            select_values_with_options(&value, ["race"], &options(None, Some(2), None)),
+            Err(LimitError::MaxSelectedNodes {
+                max_selected_nodes: 2
+            })
+        );
+        // `toys`, its object and `toys.name`
+        assert!(
+            select_values_with_options(&value, ["toys.name"], &options(None, Some(3), None))
+                .is_ok()
+        );
+        assert_eq!(
+            select_values_with_options(&value, ["toys.name"], &options(None, Some(2), None)),
+            Err(LimitError::MaxSelectedNodes {
+                max_selected_nodes: 2
+            })
+        );
+
+        let len = serde_json::to_string(&select_values(&value, ["race"]))
+            .unwrap()
+            .len();
+        assert!(
+            select_values_with_options(&value, ["race"], &options(None, None, Some(len))).is_ok()
+        );
+        assert_eq!(
+            select_values_with_options(&value, ["race"], &options(None, None, Some(len - 1))),
+            Err(LimitError::MaxOutputBytes {
+                max_output_bytes: len - 1
+            })
+        );
+
+        // the escaped characters take more bytes once serialized
+        let value: Value = json!({
+            "s": "\"\"\"\"\"\"\"\"\"\"",
+            "control\u{1}": ["\u{1}\u{1f}\n\t\\", "é\u{7f}"],
+        });
+        let value = value.as_object().unwrap();
+        let len = serde_json::to_string(value).unwrap().len();
+        let selectors = ["s", "control\u{1}"];
+        assert!(
+            select_values_with_options(value, selectors, &options(None, None, Some(len))).is_ok()
+        );
+        assert_eq!(
+            select_values_with_options(value, selectors, &options(None, None, Some(len - 1))),
+            Err(LimitError::MaxOutputBytes {
+                max_output_bytes: len - 1
+            })
+        );
+    }
 }
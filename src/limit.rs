@@ -0,0 +1,254 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::SelectOptions;
+
+/// The default maximum depth of [`SelectOptions`](crate::SelectOptions), it's the recursion
+/// limit of `serde_json`, so every json parsed by `serde_json` with its default configuration
+/// is accepted.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// The limits of [`SelectOptions`](crate::SelectOptions) that can be exceeded by a json or by
+/// a selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// The json contains more nested objects and arrays than allowed.
+    MaxDepth {
+        /// The maximum depth that was exceeded.
+        max_depth: usize,
+    },
+    /// More selectors than allowed were given.
+    MaxSelectors {
+        /// The maximum number of selectors that was exceeded.
+        max_selectors: usize,
+    },
+    /// The selection contains more values than allowed.
+    MaxSelectedNodes {
+        /// The maximum number of selected values that was exceeded.
+        max_selected_nodes: usize,
+    },
+    /// The selection would be serialized in more bytes than allowed.
+    MaxOutputBytes {
+        /// The maximum number of bytes that was exceeded.
+        max_output_bytes: usize,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::MaxDepth { max_depth } => write!(
+                f,
+                "The json contains more than {max_depth} nested objects and arrays."
+            ),
+            LimitError::MaxSelectors { max_selectors } => {
+                write!(f, "More than {max_selectors} selectors were given.")
+            }
+            LimitError::MaxSelectedNodes { max_selected_nodes } => write!(
+                f,
+                "The selection contains more than {max_selected_nodes} values."
+            ),
+            LimitError::MaxOutputBytes { max_output_bytes } => write!(
+                f,
+                "The selection would be serialized in more than {max_output_bytes} bytes."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitError {}
+
+/// Returns an error if the json contains more than `max_depth` nested objects and arrays, the
+/// json itself counts as one level.
+///
+/// The functions of this crate never recurse in a json, but `serde_json` does when a
+/// [`Value`] is cloned, compared, serialized or dropped. Call this function first when the
+/// json comes from an untrusted source to reject the jsons deep enough to overflow the stack
+/// there. The `*_with_options` functions do it for you with
+/// [`SelectOptions::max_depth`](crate::SelectOptions::max_depth).
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{check_depth, LimitError};
+///
+/// let value: Value = json!({
+///     "name": "peanut",
+///     "race": { "name": "bernese mountain" },
+///     "toys": [{ "name": "ball" }],
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// assert_eq!(check_depth(value, 3), Ok(()));
+/// assert_eq!(check_depth(value, 2), Err(LimitError::MaxDepth { max_depth: 2 }));
+/// ```
+pub fn check_depth(value: &Map<String, Value>, max_depth: usize) -> Result<(), LimitError> {
+    if max_depth == 0 {
+        return Err(LimitError::MaxDepth { max_depth });
+    }
+
+    // the check can't recurse itself, the values still to visit are kept with their depth
+    let mut to_visit: Vec<(&Value, usize)> = value.values().map(|value| (value, 2)).collect();
+
+    while let Some((value, depth)) = to_visit.pop() {
+        match value {
+            Value::Object(_) | Value::Array(_) if depth > max_depth => {
+                return Err(LimitError::MaxDepth { max_depth })
+            }
+            Value::Object(object) => {
+                to_visit.extend(object.values().map(|value| (value, depth + 1)))
+            }
+            Value::Array(array) => to_visit.extend(array.iter().map(|value| (value, depth + 1))),
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps track of the size of a selection while it's being created.
+#[derive(Debug, Default)]
+pub(crate) struct Budget {
+    max_selected_nodes: Option<usize>,
+    max_output_bytes: Option<usize>,
+    selected_nodes: usize,
+    output_bytes: usize,
+}
+
+impl Budget {
+    pub(crate) fn new(options: &SelectOptions) -> Self {
+        Self {
+            max_selected_nodes: options.max_selected_nodes,
+            max_output_bytes: options.max_output_bytes,
+            ..Self::default()
+        }
+    }
+
+    /// A budget that can't be exceeded.
+    pub(crate) fn unlimited() -> Self {
+        Self::default()
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.max_selected_nodes.is_none() && self.max_output_bytes.is_none()
+    }
+
+    /// Spends one value inserted under `key`, or in an array if `key` is `None`. Only the value
+    /// itself is spent, not the values it contains.
+    pub(crate) fn spend(&mut self, key: Option<&str>, value: &Value) -> Result<(), LimitError> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+
+        self.selected_nodes += 1;
+        // the key with its colon, or the comma separating it from its sibling
+        self.output_bytes += key.map_or(1, |key| escaped_len(key) + 2);
+        self.output_bytes += match value {
+            Value::Null => 4,
+            Value::Bool(true) => 4,
+            Value::Bool(false) => 5,
+            Value::Number(number) => number.to_string().len(),
+            Value::String(string) => escaped_len(string),
+            Value::Array(_) | Value::Object(_) => 2,
+        };
+
+        match (self.max_selected_nodes, self.max_output_bytes) {
+            (Some(max_selected_nodes), _) if self.selected_nodes > max_selected_nodes => {
+                Err(LimitError::MaxSelectedNodes { max_selected_nodes })
+            }
+            (_, Some(max_output_bytes)) if self.output_bytes > max_output_bytes => {
+                Err(LimitError::MaxOutputBytes { max_output_bytes })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Spends a value inserted under `key` and all the values it contains.
+    pub(crate) fn spend_all(&mut self, key: &str, value: &Value) -> Result<(), LimitError> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+
+        let mut to_spend = vec![(Some(key), value)];
+        while let Some((key, value)) = to_spend.pop() {
+            self.spend(key, value)?;
+            match value {
+                Value::Object(object) => to_spend.extend(
+                    object
+                        .iter()
+                        .map(|(key, value)| (Some(key.as_str()), value)),
+                ),
+                Value::Array(array) => to_spend.extend(array.iter().map(|value| (None, value))),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the length of a string serialized by `serde_json`, with its quotes and escaped
+/// characters.
+fn escaped_len(string: &str) -> usize {
+    let escaped: usize = string
+        .bytes()
+        .map(|byte| match byte {
+            b'"' | b'\\' | b'\x08' | b'\t' | b'\n' | b'\x0c' | b'\r' => 2,
+            // the other control characters are escaped as `\u00XX`
+            0x00..=0x1f => 6,
+            _ => 1,
+        })
+        .sum();
+    escaped + 2
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn depth() {
+        let value = json!({
+            "name": "peanut",
+            "race": { "name": { "first": "bernese" } },
+            "toys": [[{ "name": "ball" }], []],
+        });
+        let value = value.as_object().unwrap();
+
+        assert_eq!(check_depth(value, 4), Ok(()));
+        assert_eq!(
+            check_depth(value, 3),
+            Err(LimitError::MaxDepth { max_depth: 3 })
+        );
+        assert_eq!(
+            check_depth(value, 0),
+            Err(LimitError::MaxDepth { max_depth: 0 })
+        );
+
+        // the deepest json serde_json accepts to parse is accepted by default
+        let depth = (1..)
+            .find(|depth| {
+                let json = format!("{{\"a\":{}{}}}", "[".repeat(*depth), "]".repeat(*depth));
+                serde_json::from_str::<Map<String, Value>>(&json).is_err()
+            })
+            .unwrap();
+        let json = format!(
+            "{{\"a\":{}{}}}",
+            "[".repeat(depth - 1),
+            "]".repeat(depth - 1)
+        );
+        let value: Map<String, Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(check_depth(&value, DEFAULT_MAX_DEPTH), Ok(()));
+
+        assert_eq!(check_depth(&Map::new(), 1), Ok(()));
+        assert_eq!(
+            check_depth(&Map::new(), 0),
+            Err(LimitError::MaxDepth { max_depth: 0 })
+        );
+    }
+}
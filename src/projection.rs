@@ -0,0 +1,301 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::deep::clone_value;
+use crate::route::Step;
+use crate::{explain, select_values, Selector, SelectorError};
+
+const ALIAS_SEPARATOR: &str = " as ";
+
+/// A list of selectors where each selector can be renamed in the output.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::Projection;
+///
+/// let value: Value = json!({
+///     "name": "peanut",
+///     "age": 8,
+///     "race": {
+///         "name": "bernese mountain",
+///         "size": "80cm",
+///     },
+/// });
+///
+/// let projection = Projection::parse(["name", "race.name as breed"]).unwrap();
+/// let res: Value = projection.apply(value.as_object().unwrap()).into();
+/// assert_eq!(
+///     res,
+///     json!({
+///         "name": "peanut",
+///         "breed": "bernese mountain",
+///     })
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Projection {
+    fields: Vec<ProjectedField>,
+}
+
+/// One field of a [`Projection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectedField {
+    /// The selector used to find the values.
+    pub selector: Selector,
+    /// The key under which the values are returned, if `None` the values are returned where
+    /// they were found, as [`select_values`] does.
+    pub alias: Option<String>,
+}
+
+impl Projection {
+    /// Creates an empty projection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a list of fields, a field is either a selector or a selector followed by
+    /// ` as ` and its alias, like `race.name as breed`.
+    /// The whitespace around ` as ` is ignored, and a field ending with ` as` is rejected
+    /// since its alias is missing.
+    pub fn parse<'a>(fields: impl IntoIterator<Item = &'a str>) -> Result<Self, ProjectionError> {
+        let mut projection = Projection::new();
+        for field in fields {
+            if field.trim_end().ends_with(ALIAS_SEPARATOR.trim_end()) {
+                return Err(ProjectionError::EmptyAlias);
+            }
+
+            projection = match field.rsplit_once(ALIAS_SEPARATOR) {
+                Some((selector, alias)) => {
+                    projection.field_as(Selector::parse(selector.trim_end())?, alias.trim())
+                }
+                None => projection.field(Selector::parse(field)?),
+            };
+        }
+        Ok(projection)
+    }
+
+    /// Adds a field returned where it was found.
+    pub fn field(mut self, selector: Selector) -> Self {
+        self.fields.push(ProjectedField {
+            selector,
+            alias: None,
+        });
+        self
+    }
+
+    /// Adds a field returned under the `alias` key.
+    pub fn field_as(mut self, selector: Selector, alias: impl Into<String>) -> Self {
+        self.fields.push(ProjectedField {
+            selector,
+            alias: Some(alias.into()),
+        });
+        self
+    }
+
+    /// Returns the fields of the projection.
+    pub fn fields(&self) -> &[ProjectedField] {
+        &self.fields
+    }
+
+    /// Creates a new json containing the selected fields, the fields without an alias are
+    /// selected as with [`select_values`]. An aliased field matches the same values, as listed
+    /// by [`explain`], and is inserted at the root of the json:
+    /// - if its selector matches nothing the alias is not inserted,
+    /// - if its selector matches a single value without going through an array, this value is
+    ///   inserted,
+    /// - otherwise an array containing all the matched values in the order they appear is
+    ///   inserted.
+    ///
+    /// An alias overrides a field selected without alias, and a later alias overrides an
+    /// earlier one with the same name.
+    pub fn apply(&self, value: &Map<String, Value>) -> Map<String, Value> {
+        let selectors = self
+            .fields
+            .iter()
+            .filter(|field| field.alias.is_none())
+            .map(|field| &field.selector);
+        let mut projected = select_values(value, selectors);
+
+        for field in &self.fields {
+            let Some(alias) = &field.alias else {
+                continue;
+            };
+
+            let traces = explain(value, &field.selector);
+            let value = match traces.as_slice() {
+                [] => continue,
+                [trace] if !trace.steps.iter().any(|s| matches!(s, Step::Index(_))) => {
+                    clone_value(trace.value)
+                }
+                traces => traces
+                    .iter()
+                    .map(|trace| clone_value(trace.value))
+                    .collect::<Vec<_>>()
+                    .into(),
+            };
+            projected.insert(alias.clone(), value);
+        }
+
+        projected
+    }
+}
+
+/// The reasons a field of a [`Projection`] can be rejected by [`Projection::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectionError {
+    /// The selector of the field is invalid.
+    InvalidSelector(SelectorError),
+    /// The field ends with ` as`, without an alias after it.
+    EmptyAlias,
+}
+
+impl From<SelectorError> for ProjectionError {
+    fn from(error: SelectorError) -> Self {
+        ProjectionError::InvalidSelector(error)
+    }
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionError::InvalidSelector(error) => write!(f, "{error}"),
+            ProjectionError::EmptyAlias => write!(f, "The alias of the field is empty."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProjectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectionError::InvalidSelector(error) => Some(error),
+            ProjectionError::EmptyAlias => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_projection() {
+        let projection =
+            Projection::parse(["name", "race.name  as  breed ", "a as b as c", "as"]).unwrap();
+        assert_eq!(
+            projection.fields(),
+            [
+                ProjectedField {
+                    selector: Selector::parse("name").unwrap(),
+                    alias: None,
+                },
+                ProjectedField {
+                    selector: Selector::parse("race.name").unwrap(),
+                    alias: Some(S("breed")),
+                },
+                ProjectedField {
+                    selector: Selector::parse("a as b").unwrap(),
+                    alias: Some(S("c")),
+                },
+                ProjectedField {
+                    selector: Selector::parse("as").unwrap(),
+                    alias: None,
+                },
+            ]
+        );
+
+        for field in ["race.name as ", "race.name as", "race.name as \t", " as"] {
+            assert_eq!(Projection::parse([field]), Err(ProjectionError::EmptyAlias));
+        }
+        assert_eq!(
+            Projection::parse([" as breed"]),
+            Err(ProjectionError::InvalidSelector(SelectorError::Empty))
+        );
+        assert_eq!(
+            Projection::parse(["race. as breed"]),
+            Err(ProjectionError::InvalidSelector(
+                SelectorError::EmptySegment { position: 5 }
+            ))
+        );
+    }
+
+    #[test]
+    fn apply_aliases() {
+        let value: Value = json!({
+            "name": "peanut",
+            "race.name": "bernese mountain",
+            "race": {
+                "name": "bernese",
+                "size": "80cm",
+            },
+            "tags": ["good boy"],
+            "doggos": [
+                { "name": "marc" },
+                { "age": 4 },
+            ],
+        });
+        let value: &Map<String, Value> = value.as_object().unwrap();
+
+        let projection = Projection::parse([
+            "name",
+            "race.size as size",
+            "race.name as breed",
+            "tags as labels",
+            "doggos.name as doggos",
+            "owner.name as owner",
+            "name as race",
+            "race",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Value::from(projection.apply(value)),
+            json!({
+                "name": "peanut",
+                "size": "80cm",
+                "breed": ["bernese", "bernese mountain"],
+                "labels": ["good boy"],
+                "doggos": ["marc"],
+                "race": "peanut",
+                "race.name": "bernese mountain",
+            })
+        );
+
+        assert!(Projection::new().apply(value).is_empty());
+    }
+
+    #[test]
+    fn alias_flattened_key_under_selector() {
+        let value: Value = json!({
+            "race.name": "bernese",
+            "race": {
+                "size": "80cm",
+            },
+        });
+        let value: &Map<String, Value> = value.as_object().unwrap();
+
+        // the alias gets the same values as the selector without alias
+        let projection = Projection::parse(["race", "race as breed"]).unwrap();
+        assert_eq!(
+            Value::from(projection.apply(value)),
+            json!({
+                "race.name": "bernese",
+                "race": {
+                    "size": "80cm",
+                },
+                "breed": [{ "size": "80cm" }, "bernese"],
+            })
+        );
+
+        let projection = Projection::parse(["race.name as breed"]).unwrap();
+        assert_eq!(
+            Value::from(projection.apply(value)),
+            json!({ "breed": "bernese" })
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde_json::{Map, Value};
+
+use crate::{explain, select_values};
+
+/// Describes what each selector matched during a [`select_values_with_report`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectionReport {
+    /// The selectors that didn't match anything, in the order they were given.
+    pub unmatched: Vec<String>,
+    /// For each selector that matched something, the chains of keys leading to the values it
+    /// matched. The arrays are transparent and don't appear in the chains, a chain appears only
+    /// once even if it was matched in multiple elements of an array.
+    pub matched: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+/// Same as [`select_values`] but also returns a [`SelectionReport`] describing what each
+/// selector matched.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::select_values_with_report;
+///
+/// let value: Value = json!({
+///     "name": "peanut",
+///     "race.name": "bernese mountain",
+///     "race": {
+///         "name": "bernese mountain",
+///     },
+/// });
+/// let value: &Map<String, Value> = value.as_object().unwrap();
+///
+/// let (res, report) = select_values_with_report(value, ["race.name", "age"]);
+/// assert_eq!(
+///     Value::from(res),
+///     json!({
+///         "race.name": "bernese mountain",
+///         "race": {
+///             "name": "bernese mountain",
+///         },
+///     })
+/// );
+/// assert_eq!(report.unmatched, ["age"]);
+/// assert_eq!(report.matched["race.name"], [vec!["race", "name"], vec!["race.name"]]);
+/// ```
+pub fn select_values_with_report<'a, S: AsRef<str> + ?Sized + 'a>(
+    value: &Map<String, Value>,
+    selectors: impl IntoIterator<Item = &'a S>,
+) -> (Map<String, Value>, SelectionReport) {
+    let selectors: Vec<&str> = selectors.into_iter().map(AsRef::as_ref).collect();
+    let mut report = SelectionReport::default();
+
+    for selector in selectors.iter().copied() {
+        if report.matched.contains_key(selector) || report.unmatched.iter().any(|s| s == selector) {
+            continue;
+        }
+
+        let mut chains: Vec<Vec<String>> = Vec::new();
+        for trace in explain(value, selector) {
+            let chain: Vec<String> = trace.keys().into_iter().map(String::from).collect();
+            if !chains.contains(&chain) {
+                chains.push(chain);
+            }
+        }
+
+        if chains.is_empty() {
+            report.unmatched.push(selector.to_string());
+        } else {
+            report.matched.insert(selector.to_string(), chains);
+        }
+    }
+
+    (select_values(value, selectors), report)
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn report_matches() {
+        let value: Value = json!({
+            "pet.dog.name": "jean",
+            "pet.dog": {
+                "name": "bob",
+            },
+            "pet": {
+                "dog.name": "michel",
+                "dog": {
+                    "name": "milan",
+                }
+            },
+            "doggos": [
+                { "name": "marc" },
+                { "age": 4 },
+                [{ "name": "patou" }],
+            ],
+        });
+        let value: &Map<String, Value> = value.as_object().unwrap();
+
+        let selectors = [
+            "pet.dog.name",
+            "doggos.name",
+            "pet.cat",
+            "doggos.race",
+            "pet.cat",
+            "doggos.name",
+        ];
+        let (res, report) = select_values_with_report(value, selectors);
+        assert_eq!(res, select_values(value, selectors));
+        assert_eq!(report.unmatched, vec![S("pet.cat"), S("doggos.race")]);
+        assert_eq!(
+            report.matched,
+            BTreeMap::from([
+                (S("doggos.name"), vec![vec![S("doggos"), S("name")]]),
+                (
+                    S("pet.dog.name"),
+                    vec![
+                        vec![S("pet"), S("dog"), S("name")],
+                        vec![S("pet"), S("dog.name")],
+                        vec![S("pet.dog"), S("name")],
+                        vec![S("pet.dog.name")],
+                    ]
+                ),
+            ])
+        );
+
+        let (res, report) = select_values_with_report::<str>(value, []);
+        assert!(res.is_empty());
+        assert_eq!(report, SelectionReport::default());
+    }
+
+    #[test]
+    fn report_flattened_key_under_selected_parent() {
+        let value: Value = json!({
+            "dog.name": "jean",
+            "pet": {
+                "dog.race.name": "bernese",
+            },
+        });
+        let value: &Map<String, Value> = value.as_object().unwrap();
+
+        let (res, report) = select_values_with_report(value, ["dog", "pet.dog.race"]);
+        assert_eq!(
+            Value::from(res),
+            json!({
+                "dog.name": "jean",
+                "pet": {
+                    "dog.race.name": "bernese",
+                },
+            })
+        );
+        assert!(report.unmatched.is_empty());
+        assert_eq!(
+            report.matched,
+            BTreeMap::from([
+                (S("dog"), vec![vec![S("dog.name")]]),
+                (S("pet.dog.race"), vec![vec![S("pet"), S("dog.race.name")]]),
+            ])
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::slice;
+
+use serde_json::{map, Value};
+
+use crate::selection::{join_path, Selection};
+use crate::Document;
+
+/// One step of the route leading from the root of a json to one of its values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Go in the field of an object.
+    Key(String),
+    /// Go in the element of an array.
+    Index(usize),
+}
+
+pub(crate) type Route = Vec<Step>;
+
+/// Returns the route of every value selected by the selector, following the same rules as
+/// [`select_values`](crate::select_values), in the order they appear in the json.
+/// Only the outermost selected values are returned, not the values they contain.
+/// The routes never stop in an array, if a selected field contains an array, the route stops on the field.
+pub(crate) fn find_routes(value: &Document, selector: &str) -> Vec<Route> {
+    let mut routes = Vec::new();
+
+    // the objects and arrays being traversed with their path, `route` leads to the last one,
+    // the json can be too deep to recurse
+    let mut route = Vec::new();
+    let mut stack = vec![(Children::Object(value.iter()), String::new())];
+    while let Some((children, path)) = stack.last_mut() {
+        let Some((step, value)) = children.next() else {
+            stack.pop();
+            route.pop();
+            continue;
+        };
+
+        let (path, selection) = match &step {
+            Step::Key(key) => {
+                let path = join_path(path, key);
+                let selection = Selection::of(&path, &[selector]);
+                (path, selection)
+            }
+            // the arrays are transparent
+            Step::Index(_) => (path.clone(), Selection::Traversed),
+        };
+        route.push(step);
+        match (selection, Children::of(value)) {
+            (Selection::Selected, _) => {
+                routes.push(route.clone());
+                route.pop();
+            }
+            (Selection::Traversed, Some(children)) => stack.push((children, path)),
+            _ => {
+                route.pop();
+            }
+        }
+    }
+
+    routes
+}
+
+/// Same as [`find_routes`] but only returns the routes whose path is exactly the selector, the
+/// flattened keys nested under the selector, like `dog.name` for the selector `dog`, are
+/// ignored.
+pub(crate) fn find_exact_routes(value: &Document, selector: &str) -> Vec<Route> {
+    let mut routes = find_routes(value, selector);
+    routes.retain(|route| path_of(route) == selector);
+    routes
+}
+
+/// Returns the dotted path of the value a route leads to.
+fn path_of(route: &[Step]) -> String {
+    route.iter().fold(String::new(), |path, step| match step {
+        Step::Key(key) => join_path(&path, key),
+        Step::Index(_) => path,
+    })
+}
+
+/// The values contained in an object or an array, with the step leading to each of them.
+enum Children<'a> {
+    Object(map::Iter<'a>),
+    Array(Enumerate<slice::Iter<'a, Value>>),
+}
+
+impl<'a> Children<'a> {
+    fn of(value: &'a Value) -> Option<Self> {
+        match value {
+            Value::Object(object) => Some(Children::Object(object.iter())),
+            Value::Array(array) => Some(Children::Array(array.iter().enumerate())),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Option<(Step, &'a Value)> {
+        match self {
+            Children::Object(iter) => iter
+                .next()
+                .map(|(key, value)| (Step::Key(key.to_string()), value)),
+            Children::Array(iter) => iter
+                .next()
+                .map(|(index, value)| (Step::Index(index), value)),
+        }
+    }
+}
+
+/// Follows a route returned by [`find_routes`] on the same json.
+pub(crate) fn follow<'a>(value: &'a Document, route: &[Step]) -> Option<&'a Value> {
+    let (first, route) = route.split_first()?;
+    let mut value = match first {
+        Step::Key(key) => value.get(key)?,
+        Step::Index(_) => return None,
+    };
+
+    for step in route {
+        value = match step {
+            Step::Key(key) => value.as_object()?.get(key)?,
+            Step::Index(index) => value.as_array()?.get(*index)?,
+        };
+    }
+
+    Some(value)
+}
+
+/// Follows a route returned by [`find_routes`] on the same json.
+pub(crate) fn follow_mut<'a>(value: &'a mut Document, route: &[Step]) -> Option<&'a mut Value> {
+    let (first, route) = route.split_first()?;
+    let mut value = match first {
+        Step::Key(key) => value.get_mut(key)?,
+        Step::Index(_) => return None,
+    };
+
+    for step in route {
+        value = match step {
+            Step::Key(key) => value.as_object_mut()?.get_mut(key)?,
+            Step::Index(index) => value.as_array_mut()?.get_mut(*index)?,
+        };
+    }
+
+    Some(value)
+}
@@ -0,0 +1,78 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::{contained_in, SPLIT_SYMBOL};
+
+/// How a list of selectors treats the value found at a path.
+/// This is the single rule followed by [`select_values`](crate::select_values) and
+/// [`map_leaf_values`](crate::map_leaf_values), see the crate documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Selection {
+    /// A selector is equal to the path or to one of its parents, the value and everything it
+    /// contains is selected.
+    Selected,
+    /// A selector goes deeper than the path, the value must be traversed to find the
+    /// selected values.
+    Traversed,
+    /// No selector concerns the path.
+    Skipped,
+}
+
+impl Selection {
+    pub(crate) fn of(path: &str, selectors: &[&str]) -> Self {
+        if selectors
+            .iter()
+            .any(|selector| contained_in(path, selector))
+        {
+            Selection::Selected
+        } else if selectors
+            .iter()
+            .any(|selector| contained_in(selector, path))
+        {
+            Selection::Traversed
+        } else {
+            Selection::Skipped
+        }
+    }
+}
+
+/// Returns the path of a field from the path of its parent, an empty `base_key` is the root.
+pub(crate) fn join_path(base_key: &str, key: &str) -> String {
+    if base_key.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}{}{}", base_key, SPLIT_SYMBOL, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection() {
+        let selectors = ["dog.name", "cat", "pet.dog.race"];
+
+        assert_eq!(Selection::of("dog.name", &selectors), Selection::Selected);
+        assert_eq!(
+            Selection::of("dog.name.first", &selectors),
+            Selection::Selected
+        );
+        assert_eq!(Selection::of("cat", &selectors), Selection::Selected);
+        assert_eq!(Selection::of("cat.name", &selectors), Selection::Selected);
+
+        assert_eq!(Selection::of("dog", &selectors), Selection::Traversed);
+        assert_eq!(Selection::of("pet", &selectors), Selection::Traversed);
+        assert_eq!(Selection::of("pet.dog", &selectors), Selection::Traversed);
+
+        assert_eq!(Selection::of("dog.age", &selectors), Selection::Skipped);
+        assert_eq!(Selection::of("do", &selectors), Selection::Skipped);
+        assert_eq!(Selection::of("dog.names", &selectors), Selection::Skipped);
+        assert_eq!(Selection::of("cats", &selectors), Selection::Skipped);
+        assert_eq!(Selection::of("pet.cat", &selectors), Selection::Skipped);
+        assert_eq!(Selection::of("cat", &[]), Selection::Skipped);
+
+        assert_eq!(join_path("", "dog"), "dog");
+        assert_eq!(join_path("pet", "dog.name"), "pet.dog.name");
+    }
+}
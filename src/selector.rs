@@ -0,0 +1,193 @@
+use alloc::string::{String, ToString};
+use core::borrow::Borrow;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::SPLIT_SYMBOL;
+
+/// A validated selector.
+/// It can be used anywhere a `&str` selector is accepted and avoids re-validating user input
+/// every time it's used.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values, Selector, SelectorError};
+///
+/// let selector = Selector::parse("race.name").unwrap();
+/// assert_eq!(selector.segments().collect::<Vec<_>>(), ["race", "name"]);
+///
+/// let value: Value = json!({ "race": { "name": "bernese mountain", "size": "80cm" } });
+/// let res: Value = select_values(value.as_object().unwrap(), [&selector]).into();
+/// assert_eq!(res, json!({ "race": { "name": "bernese mountain" } }));
+///
+/// assert_eq!(Selector::parse(""), Err(SelectorError::Empty));
+/// assert_eq!(Selector::parse("race..name"), Err(SelectorError::EmptySegment { position: 5 }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Selector(String);
+
+impl Selector {
+    /// Validates a selector.
+    /// A selector can't be empty and can't contain empty segments, meaning it can't start or
+    /// end with a `.` or contain two consecutive `.`.
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        if selector.is_empty() {
+            return Err(SelectorError::Empty);
+        }
+
+        let mut position = 0;
+        for segment in selector.split(SPLIT_SYMBOL) {
+            if segment.is_empty() {
+                return Err(SelectorError::EmptySegment { position });
+            }
+            position += segment.len() + SPLIT_SYMBOL.len_utf8();
+        }
+
+        Ok(Self(selector.to_string()))
+    }
+
+    /// Returns the selector as it was written.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Iterates over the segments of the selector, `race.name` is made of `race` and `name`.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split(SPLIT_SYMBOL)
+    }
+}
+
+impl AsRef<str> for Selector {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Selector {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<Selector> for String {
+    fn from(selector: Selector) -> Self {
+        selector.0
+    }
+}
+
+/// The reasons a selector can be rejected by [`Selector::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorError {
+    /// The selector is an empty string.
+    Empty,
+    /// The selector contains an empty segment, `position` is the byte offset where this
+    /// empty segment starts.
+    EmptySegment { position: usize },
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorError::Empty => write!(f, "The selector is empty."),
+            SelectorError::EmptySegment { position } => write!(
+                f,
+                "The selector contains an empty segment at position {position}."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelectorError {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Selector {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Selector {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let selector = String::deserialize(deserializer)?;
+        Selector::parse(&selector).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_selector() {
+        assert_eq!(Selector::parse("name").unwrap().as_str(), "name");
+        assert_eq!(
+            Selector::parse("animaux.chien.race.bouvier bernois")
+                .unwrap()
+                .segments()
+                .collect::<Vec<_>>(),
+            vec!["animaux", "chien", "race", "bouvier bernois"]
+        );
+        assert_eq!(
+            "chien.âge".parse::<Selector>().unwrap().to_string(),
+            "chien.âge"
+        );
+
+        // -- the wrongs
+        assert_eq!(Selector::parse(""), Err(SelectorError::Empty));
+        assert_eq!(
+            Selector::parse("."),
+            Err(SelectorError::EmptySegment { position: 0 })
+        );
+        assert_eq!(
+            Selector::parse("..title"),
+            Err(SelectorError::EmptySegment { position: 0 })
+        );
+        assert_eq!(
+            Selector::parse("name."),
+            Err(SelectorError::EmptySegment { position: 5 })
+        );
+        assert_eq!(
+            Selector::parse("âge..name"),
+            Err(SelectorError::EmptySegment { position: 5 })
+        );
+        assert_eq!(
+            Selector::parse("race.name..").unwrap_err().to_string(),
+            "The selector contains an empty segment at position 10."
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_selector() {
+        use serde_json::json;
+
+        let selector = Selector::parse("race.name").unwrap();
+        assert_eq!(serde_json::to_value(&selector).unwrap(), json!("race.name"));
+        assert_eq!(
+            serde_json::from_value::<Selector>(json!("race.name")).unwrap(),
+            selector
+        );
+        assert_eq!(
+            serde_json::from_value::<Selector>(json!("race..name"))
+                .unwrap_err()
+                .to_string(),
+            "The selector contains an empty segment at position 5."
+        );
+        assert!(serde_json::from_value::<Selector>(json!(12)).is_err());
+    }
+}
@@ -0,0 +1,523 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{contained_in, Selector, SelectorError};
+
+const LIST_SEPARATOR: char = ',';
+const ESCAPE_SYMBOL: char = '\\';
+
+/// A list of validated selectors.
+/// It can be given to any function accepting a list of selectors.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values, SelectorSet};
+///
+/// let mut selectors = SelectorSet::parse(["race.name", "name", "race"]).unwrap();
+/// selectors.normalize();
+/// assert_eq!(selectors.iter().map(|s| s.as_str()).collect::<Vec<_>>(), ["name", "race"]);
+///
+/// let value: Value = json!({ "name": "peanut", "age": 8 });
+/// let res: Value = select_values(value.as_object().unwrap(), &selectors).into();
+/// assert_eq!(res, json!({ "name": "peanut" }));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct SelectorSet {
+    selectors: Vec<Selector>,
+}
+
+impl SelectorSet {
+    /// Creates an empty set of selectors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates all the selectors and creates a set from them.
+    pub fn parse<'a>(selectors: impl IntoIterator<Item = &'a str>) -> Result<Self, SelectorError> {
+        selectors.into_iter().map(Selector::parse).collect()
+    }
+
+    /// Adds a selector at the end of the set.
+    pub fn insert(&mut self, selector: Selector) {
+        self.selectors.push(selector);
+    }
+
+    /// Returns `true` if this exact selector is in the set.
+    pub fn contains(&self, selector: &str) -> bool {
+        self.selectors.iter().any(|s| s.as_str() == selector)
+    }
+
+    /// Iterates over the selectors of the set.
+    pub fn iter(&self) -> core::slice::Iter<'_, Selector> {
+        self.selectors.iter()
+    }
+
+    /// Returns the selectors of the set.
+    pub fn as_slice(&self) -> &[Selector] {
+        &self.selectors
+    }
+
+    /// Returns the number of selectors in the set.
+    pub fn len(&self) -> usize {
+        self.selectors.len()
+    }
+
+    /// Returns `true` if the set doesn't contain any selector.
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    /// Reduces the set to the minimal list of selectors selecting the same fields.
+    /// All the selectors nested in another selector of the set are removed, `dog.name` is
+    /// removed if `dog` is in the set, then the selectors are sorted and deduplicated.
+    /// Two sets selecting the same fields are equal once normalized.
+    pub fn normalize(&mut self) {
+        normalize(&mut self.selectors);
+    }
+
+    /// Returns the normalized set of the fields selected by either set.
+    /// ```
+    /// use permissive_json_pointer::SelectorSet;
+    ///
+    /// let left = SelectorSet::parse(["name", "race.name"]).unwrap();
+    /// let right = SelectorSet::parse(["race", "age"]).unwrap();
+    /// assert_eq!(left.union(&right), SelectorSet::parse(["age", "name", "race"]).unwrap());
+    /// ```
+    pub fn union(&self, other: &SelectorSet) -> SelectorSet {
+        let mut union: SelectorSet = self.iter().chain(other).cloned().collect();
+        union.normalize();
+        union
+    }
+
+    /// Returns the normalized set of the fields selected by both sets.
+    /// When a selector is nested in a selector of the other set, only the nested one is kept.
+    /// ```
+    /// use permissive_json_pointer::SelectorSet;
+    ///
+    /// let left = SelectorSet::parse(["name", "race"]).unwrap();
+    /// let right = SelectorSet::parse(["race.name", "age"]).unwrap();
+    /// assert_eq!(left.intersection(&right), SelectorSet::parse(["race.name"]).unwrap());
+    /// ```
+    pub fn intersection(&self, other: &SelectorSet) -> SelectorSet {
+        let mut intersection = SelectorSet::new();
+        for left in self {
+            for right in other {
+                if contained_in(left.as_str(), right.as_str()) {
+                    intersection.insert(left.clone());
+                } else if contained_in(right.as_str(), left.as_str()) {
+                    intersection.insert(right.clone());
+                }
+            }
+        }
+        intersection.normalize();
+        intersection
+    }
+
+    /// Returns the normalized set of the selectors of `self` that are not nested in a selector
+    /// of `other`.
+    /// There is no way to express "everything in `race` but `race.name`", thus a selector
+    /// containing a selector of `other` is kept entirely.
+    /// ```
+    /// use permissive_json_pointer::SelectorSet;
+    ///
+    /// let left = SelectorSet::parse(["name", "race.name", "age"]).unwrap();
+    /// let right = SelectorSet::parse(["race", "age.years"]).unwrap();
+    /// assert_eq!(left.difference(&right), SelectorSet::parse(["age", "name"]).unwrap());
+    /// ```
+    pub fn difference(&self, other: &SelectorSet) -> SelectorSet {
+        let mut difference: SelectorSet = self
+            .iter()
+            .filter(|left| {
+                !other
+                    .iter()
+                    .any(|right| contained_in(left.as_str(), right.as_str()))
+            })
+            .cloned()
+            .collect();
+        difference.normalize();
+        difference
+    }
+
+    /// Returns `true` if every field selected by `other` is also selected by `self`.
+    /// ```
+    /// use permissive_json_pointer::SelectorSet;
+    ///
+    /// let allowed = SelectorSet::parse(["name", "race"]).unwrap();
+    /// assert!(allowed.covers(&SelectorSet::parse(["race.name", "name"]).unwrap()));
+    /// assert!(!allowed.covers(&SelectorSet::parse(["race.name", "age"]).unwrap()));
+    /// ```
+    pub fn covers(&self, other: &SelectorSet) -> bool {
+        other.covered_by(self).is_empty()
+    }
+
+    /// Returns the selectors of `self` that are not nested in any selector of `other`, in the
+    /// order they appear in `self`. An empty list means `other` covers `self`.
+    /// ```
+    /// use permissive_json_pointer::{Selector, SelectorSet};
+    ///
+    /// let allowed = SelectorSet::parse(["name", "race"]).unwrap();
+    /// let requested = SelectorSet::parse(["race.name", "age", "name"]).unwrap();
+    /// assert_eq!(requested.covered_by(&allowed), [Selector::parse("age").unwrap()]);
+    /// ```
+    pub fn covered_by(&self, other: &SelectorSet) -> Vec<Selector> {
+        self.iter()
+            .filter(|selector| {
+                !other
+                    .iter()
+                    .any(|other| contained_in(selector.as_str(), other.as_str()))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Removes the selectors nested in another one, then sorts and deduplicates the rest, see
+/// [`SelectorSet::normalize`].
+pub(crate) fn normalize<S: AsRef<str> + Ord + Clone>(selectors: &mut Vec<S>) {
+    selectors.sort_unstable();
+    selectors.dedup();
+
+    let all = core::mem::take(selectors);
+    *selectors = all
+        .iter()
+        .filter(|selector| {
+            !all.iter()
+                .any(|other| other != *selector && contained_in(selector.as_ref(), other.as_ref()))
+        })
+        .cloned()
+        .collect();
+}
+
+impl FromIterator<Selector> for SelectorSet {
+    fn from_iter<T: IntoIterator<Item = Selector>>(iter: T) -> Self {
+        Self {
+            selectors: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Selector> for SelectorSet {
+    fn extend<T: IntoIterator<Item = Selector>>(&mut self, iter: T) {
+        self.selectors.extend(iter)
+    }
+}
+
+impl IntoIterator for SelectorSet {
+    type Item = Selector;
+    type IntoIter = alloc::vec::IntoIter<Selector>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.selectors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SelectorSet {
+    type Item = &'a Selector;
+    type IntoIter = core::slice::Iter<'a, Selector>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Parses a comma-separated list of selectors, as found in a query string like
+/// `?fields=name,race.name`.
+/// A `,` that is part of a key must be escaped as `\,`, and a `\` as `\\`.
+/// An empty string is an empty list.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::{select_values, SelectorSet};
+///
+/// let selectors: SelectorSet = r"name,race.name,size\,weight".parse().unwrap();
+/// assert_eq!(selectors, SelectorSet::parse(["name", "race.name", "size,weight"]).unwrap());
+/// assert_eq!(selectors.to_string(), r"name,race.name,size\,weight");
+///
+/// let value: Value = json!({ "name": "peanut", "size,weight": "80cm,50kg", "age": 8 });
+/// let res: Value = select_values(value.as_object().unwrap(), &selectors).into();
+/// assert_eq!(res, json!({ "name": "peanut", "size,weight": "80cm,50kg" }));
+/// ```
+impl FromStr for SelectorSet {
+    type Err = SelectorListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut selectors = SelectorSet::new();
+        if s.is_empty() {
+            return Ok(selectors);
+        }
+
+        let mut start = 0;
+        let mut selector = String::new();
+        let mut chars = s.char_indices();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                ESCAPE_SYMBOL => match chars.next() {
+                    Some((_, c @ (LIST_SEPARATOR | ESCAPE_SYMBOL))) => selector.push(c),
+                    _ => return Err(SelectorListError::InvalidEscape { position }),
+                },
+                LIST_SEPARATOR => {
+                    selectors.insert(parse_list_item(&s[start..position], start, &selector)?);
+                    selector.clear();
+                    start = position + LIST_SEPARATOR.len_utf8();
+                }
+                c => selector.push(c),
+            }
+        }
+        selectors.insert(parse_list_item(&s[start..], start, &selector)?);
+
+        Ok(selectors)
+    }
+}
+
+/// Validates one selector of a list, the raw selector still contains the escape sequences so
+/// the positions of the errors match the list.
+fn parse_list_item(
+    raw: &str,
+    position: usize,
+    selector: &str,
+) -> Result<Selector, SelectorListError> {
+    let error = |error| SelectorListError::InvalidSelector { position, error };
+    Selector::parse(raw).map_err(error)?;
+    Selector::parse(selector).map_err(error)
+}
+
+/// Displays the set as a comma-separated list of selectors that can be parsed back.
+impl fmt::Display for SelectorSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, selector) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, "{LIST_SEPARATOR}")?;
+            }
+            for c in selector.as_str().chars() {
+                if matches!(c, LIST_SEPARATOR | ESCAPE_SYMBOL) {
+                    write!(f, "{ESCAPE_SYMBOL}")?;
+                }
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reasons a comma-separated list of selectors can be rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorListError {
+    /// A `\` is followed by something else than a `,` or a `\`, `position` is the byte offset of
+    /// the `\` in the list.
+    InvalidEscape { position: usize },
+    /// One of the selectors is invalid, `position` is the byte offset where this selector
+    /// starts in the list.
+    InvalidSelector {
+        position: usize,
+        error: SelectorError,
+    },
+}
+
+impl fmt::Display for SelectorListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorListError::InvalidEscape { position } => write!(
+                f,
+                "Invalid escape sequence at position {position}, only `\\,` and `\\\\` are allowed."
+            ),
+            SelectorListError::InvalidSelector { position, error } => {
+                write!(f, "Invalid selector at position {position}: {error}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelectorListError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SelectorListError::InvalidEscape { .. } => None,
+            SelectorListError::InvalidSelector { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Serialized as a list of selectors.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SelectorSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SelectorSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            selectors: Vec::deserialize(deserializer)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(selectors: &[&str]) -> SelectorSet {
+        SelectorSet::parse(selectors.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn normalize() {
+        let mut selectors = set(&[
+            "dog.age",
+            "dog",
+            "dog.name",
+            "cat.name",
+            "dog-walker",
+            "dog",
+        ]);
+        selectors.normalize();
+        assert_eq!(selectors, set(&["cat.name", "dog", "dog-walker"]));
+
+        let mut selectors = set(&["pet.dog.name", "pet.dog", "pet.do", "pet.dog.age"]);
+        selectors.normalize();
+        assert_eq!(selectors, set(&["pet.do", "pet.dog"]));
+
+        let mut left = set(&["race.name", "name", "race.size", "name"]);
+        let mut right = set(&["name", "race.size", "race.name"]);
+        assert_ne!(left, right);
+        left.normalize();
+        right.normalize();
+        assert_eq!(left, right);
+
+        let mut selectors = SelectorSet::new();
+        selectors.normalize();
+        assert!(selectors.is_empty());
+    }
+
+    #[test]
+    fn set_algebra() {
+        let tenant = set(&["name", "race", "doggos.age", "owner.address"]);
+        let request = set(&["race.name", "doggos", "owner", "age", "name"]);
+
+        assert_eq!(
+            tenant.union(&request),
+            set(&["age", "doggos", "name", "owner", "race"])
+        );
+        assert_eq!(
+            tenant.intersection(&request),
+            set(&["doggos.age", "name", "owner.address", "race.name"])
+        );
+        assert_eq!(request.intersection(&tenant), tenant.intersection(&request));
+        assert_eq!(tenant.difference(&request), set(&["race"]));
+        assert_eq!(
+            request.difference(&tenant),
+            set(&["age", "doggos", "owner"])
+        );
+
+        assert_eq!(set(&["a"]).intersection(&set(&["a.b"])), set(&["a.b"]));
+        assert_eq!(set(&["a"]).intersection(&set(&["ab"])), SelectorSet::new());
+        assert_eq!(set(&["a.b"]).difference(&set(&["a"])), SelectorSet::new());
+        assert_eq!(set(&["a"]).union(&SelectorSet::new()), set(&["a"]));
+    }
+
+    #[test]
+    fn coverage() {
+        let allowed = set(&["name", "race", "doggos.age"]);
+
+        assert!(allowed.covers(&set(&["name", "race.name", "race.size", "doggos.age"])));
+        assert!(allowed.covers(&allowed));
+        assert!(allowed.covers(&SelectorSet::new()));
+        assert!(!SelectorSet::new().covers(&allowed));
+
+        let requested = set(&["doggos", "race.name", "age", "doggos.age.years", "names"]);
+        assert!(!allowed.covers(&requested));
+        assert_eq!(
+            requested.covered_by(&allowed),
+            vec![
+                Selector::parse("doggos").unwrap(),
+                Selector::parse("age").unwrap(),
+                Selector::parse("names").unwrap(),
+            ]
+        );
+        assert!(requested.covered_by(&requested).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_selector_set() {
+        use serde_json::json;
+
+        let selectors = set(&["name", "race.name", "doggos.age"]);
+        let value = serde_json::to_value(&selectors).unwrap();
+        assert_eq!(value, json!(["name", "race.name", "doggos.age"]));
+        assert_eq!(
+            serde_json::from_value::<SelectorSet>(value).unwrap(),
+            selectors
+        );
+        assert_eq!(
+            serde_json::from_value::<SelectorSet>(json!([])).unwrap(),
+            SelectorSet::new()
+        );
+        assert_eq!(
+            serde_json::from_value::<SelectorSet>(json!(["name", ""]))
+                .unwrap_err()
+                .to_string(),
+            "The selector is empty."
+        );
+        assert!(serde_json::from_value::<SelectorSet>(json!("name")).is_err());
+    }
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            "name,race.name,pets.age".parse::<SelectorSet>().unwrap(),
+            set(&["name", "race.name", "pets.age"])
+        );
+        assert_eq!("".parse::<SelectorSet>().unwrap(), SelectorSet::new());
+        assert_eq!(
+            r"a\,b,c\\d,e\\,f\\\,".parse::<SelectorSet>().unwrap(),
+            set(&["a,b", r"c\d", "e\\", r"f\,"])
+        );
+        assert_eq!(
+            "bouvier bernois.âge".parse::<SelectorSet>().unwrap(),
+            set(&["bouvier bernois.âge"])
+        );
+
+        for list in ["name", "name,race.name", r"a\,b,c\\d,e\\,f\\\,", ""] {
+            let selectors: SelectorSet = list.parse().unwrap();
+            assert_eq!(selectors.to_string(), list);
+        }
+
+        // -- the wrongs
+        assert_eq!(
+            "name,,age".parse::<SelectorSet>(),
+            Err(SelectorListError::InvalidSelector {
+                position: 5,
+                error: SelectorError::Empty,
+            })
+        );
+        assert_eq!(
+            "name,".parse::<SelectorSet>(),
+            Err(SelectorListError::InvalidSelector {
+                position: 5,
+                error: SelectorError::Empty,
+            })
+        );
+        assert_eq!(
+            r"name,a\,b..c".parse::<SelectorSet>(),
+            Err(SelectorListError::InvalidSelector {
+                position: 5,
+                error: SelectorError::EmptySegment { position: 5 },
+            })
+        );
+        assert_eq!(
+            r"name,a\b".parse::<SelectorSet>(),
+            Err(SelectorListError::InvalidEscape { position: 6 })
+        );
+        assert_eq!(
+            r"name\".parse::<SelectorSet>(),
+            Err(SelectorListError::InvalidEscape { position: 4 })
+        );
+        assert_eq!(
+            "name,race.".parse::<SelectorSet>().unwrap_err().to_string(),
+            "Invalid selector at position 5: The selector contains an empty segment at position 5."
+        );
+    }
+}
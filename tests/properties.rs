@@ -0,0 +1,252 @@
+use permissive_json_pointer::{
+    field_paths, map_leaf_values_with_options, select_values, select_values_with_options,
+    LimitError, OutputShape, ScalarsInArrays, SelectOptions, SelectorSet,
+};
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+/// Keys mixing nested and flattened versions of the same paths.
+fn key() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("a"),
+        Just("b"),
+        Just("c"),
+        Just("a.b"),
+        Just("b.c"),
+        Just("a.b.c"),
+    ]
+    .prop_map(String::from)
+}
+
+fn scalar() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        "[a-z]{0,4}".prop_map(Value::from),
+    ]
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    scalar().prop_recursive(4, 64, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map(key(), inner, 0..4)
+                .prop_map(|object| Value::Object(object.into_iter().collect())),
+        ]
+    })
+}
+
+fn document() -> impl Strategy<Value = Map<String, Value>> {
+    prop::collection::btree_map(key(), value(), 0..5)
+        .prop_map(|object| object.into_iter().collect())
+}
+
+fn selectors() -> impl Strategy<Value = Vec<String>> {
+    let selector = prop::collection::vec(prop_oneof![Just("a"), Just("b"), Just("c")], 1..4)
+        .prop_map(|segments| segments.join("."));
+    prop::collection::vec(selector, 0..4)
+}
+
+fn selector_set() -> impl Strategy<Value = SelectorSet> {
+    selectors()
+        .prop_map(|selectors| SelectorSet::parse(selectors.iter().map(String::as_str)).unwrap())
+}
+
+fn scalars_in_arrays() -> impl Strategy<Value = ScalarsInArrays> {
+    prop_oneof![Just(ScalarsInArrays::Drop), Just(ScalarsInArrays::Keep)]
+}
+
+/// Returns all the scalars of a json with their dotted path.
+fn leaves(value: &Map<String, Value>) -> Vec<(String, Value)> {
+    fn leaves_in_value(value: &Value, path: &str, leaves: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    leaves_in_value(value, &path, leaves);
+                }
+            }
+            Value::Array(array) => array
+                .iter()
+                .for_each(|value| leaves_in_value(value, path, leaves)),
+            value => leaves.push((path.to_string(), value.clone())),
+        }
+    }
+
+    let mut res = Vec::new();
+    leaves_in_value(&Value::Object(value.clone()), "", &mut res);
+    res
+}
+
+/// Returns the number of values in a json, including the json itself.
+fn node_count(value: &Value) -> usize {
+    match value {
+        Value::Object(object) => 1 + object.values().map(node_count).sum::<usize>(),
+        Value::Array(array) => 1 + array.iter().map(node_count).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+fn sorted(mut leaves: Vec<(String, Value)>) -> Vec<(String, String)> {
+    let mut leaves: Vec<_> = leaves
+        .drain(..)
+        .map(|(path, value)| (path, value.to_string()))
+        .collect();
+    leaves.sort();
+    leaves
+}
+
+proptest! {
+    #[test]
+    fn mapper_visits_the_selected_leaves(
+        document in document(),
+        selectors in selectors(),
+        scalars_in_arrays in scalars_in_arrays(),
+    ) {
+        let options = SelectOptions { scalars_in_arrays, ..SelectOptions::default() };
+        let selected = select_values_with_options(&document, &selectors, &options).unwrap();
+
+        let mut visited = Vec::new();
+        let mut document = document;
+        map_leaf_values_with_options(&mut document, &selectors, &options, |path, value| {
+            visited.push((path.to_string(), value.clone()))
+        })
+        .unwrap();
+
+        prop_assert_eq!(sorted(visited), sorted(leaves(&selected)));
+    }
+
+    #[test]
+    fn select_values_matches_a_naive_implementation(
+        document in document(),
+        selectors in selectors(),
+    ) {
+        // a leaf is selected if a selector is equal to its path or to the path of a parent
+        let expected: Vec<_> = leaves(&document)
+            .into_iter()
+            .filter(|(path, _)| {
+                selectors.iter().any(|selector| {
+                    path == selector || path.starts_with(&format!("{selector}."))
+                })
+            })
+            .collect();
+
+        prop_assert_eq!(
+            sorted(leaves(&select_values(&document, &selectors))),
+            sorted(expected)
+        );
+    }
+
+    #[test]
+    fn select_values_is_idempotent(document in document(), selectors in selectors()) {
+        let selected = select_values(&document, &selectors);
+        prop_assert_eq!(select_values(&selected, &selectors), selected);
+    }
+
+    #[test]
+    fn selecting_every_top_level_key_returns_the_document(document in document()) {
+        prop_assert_eq!(select_values(&document, document.keys()), document.clone());
+    }
+
+    #[test]
+    fn selecting_every_path_returns_the_document(document in document()) {
+        let paths = field_paths(&document, true);
+        prop_assert_eq!(select_values(&document, &paths), document.clone());
+    }
+
+    #[test]
+    fn select_values_is_monotonic(
+        document in document(),
+        selectors in selectors(),
+        more in selectors(),
+    ) {
+        let all: Vec<_> = selectors.iter().chain(&more).collect();
+        let small = leaves(&select_values(&document, &selectors));
+        let big = leaves(&select_values(&document, all.iter().copied()));
+        for leaf in small {
+            prop_assert!(big.contains(&leaf), "{:?} is missing", leaf);
+        }
+
+        // selecting in a bigger selection is the same as selecting in the document
+        let selected = select_values(&document, all.iter().copied());
+        prop_assert_eq!(
+            select_values(&selected, &selectors),
+            select_values(&document, &selectors)
+        );
+    }
+
+    #[test]
+    fn flattened_shape_contains_the_selected_leaves(
+        document in document(),
+        selectors in selectors(),
+    ) {
+        let options = SelectOptions { shape: OutputShape::Flattened, ..SelectOptions::default() };
+        let flattened = select_values_with_options(&document, &selectors, &options).unwrap();
+        let nested = select_values(&document, &selectors);
+
+        // the flattened json has the same leaves, with the path of the nested leaves as key
+        let flattened_leaves = leaves(&flattened);
+        prop_assert_eq!(sorted(flattened_leaves), sorted(leaves(&nested)));
+    }
+
+    #[test]
+    fn normalization_keeps_the_selection(document in document(), selectors in selector_set()) {
+        let mut normalized = selectors.clone();
+        normalized.normalize();
+        prop_assert_eq!(
+            select_values(&document, &normalized),
+            select_values(&document, &selectors)
+        );
+    }
+
+    #[test]
+    fn set_algebra_matches_the_selection(
+        document in document(),
+        left in selector_set(),
+        right in selector_set(),
+    ) {
+        let both: Vec<_> = left.iter().chain(&right).collect();
+        prop_assert_eq!(
+            select_values(&document, &left.union(&right)),
+            select_values(&document, both)
+        );
+
+        prop_assert_eq!(
+            select_values(&document, &left.intersection(&right)),
+            select_values(&select_values(&document, &left), &right)
+        );
+
+        if left.covers(&right) {
+            prop_assert_eq!(
+                select_values(&select_values(&document, &left), &right),
+                select_values(&document, &right)
+            );
+        }
+    }
+
+    #[test]
+    fn max_selected_nodes_counts_the_selected_values(
+        document in document(),
+        selectors in selectors(),
+        max_selected_nodes in 0..32usize,
+    ) {
+        let selected = select_values(&document, &selectors);
+        let nodes = node_count(&Value::Object(selected.clone())) - 1;
+
+        let options = SelectOptions {
+            max_selected_nodes: Some(max_selected_nodes),
+            ..SelectOptions::default()
+        };
+        let res = select_values_with_options(&document, &selectors, &options);
+        if nodes <= max_selected_nodes {
+            prop_assert_eq!(res, Ok(selected));
+        } else {
+            prop_assert_eq!(res, Err(LimitError::MaxSelectedNodes { max_selected_nodes }));
+        }
+    }
+}